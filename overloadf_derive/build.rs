@@ -0,0 +1,21 @@
+// `proc_macro::Diagnostic` is only available on nightly. other toolchains report errors through
+// `compile_error!` instead (see `src/diagnostic.rs`), so the macros build on any toolchain,
+// whatever backend the expanded code is for.
+use std::env;
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rustc-check-cfg=cfg(nightly)");
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let nightly = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .map(|output| {
+            let version = String::from_utf8_lossy(&output.stdout);
+            version.contains("nightly") || version.contains("-dev")
+        })
+        .unwrap_or(false);
+    if nightly {
+        println!("cargo:rustc-cfg=nightly");
+    }
+}
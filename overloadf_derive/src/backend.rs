@@ -0,0 +1,21 @@
+// which backend the expansion in progress generates code for. the default one implements the
+// nightly-only Fn* traits, `#[overload(stable)]` a sealed dispatch trait and the `call` entry
+// point instead (see `dispatch_declaration`). the backend is picked by the attribute of every
+// expansion, so crates that use different backends can be built together.
+use std::cell::Cell;
+
+thread_local! {
+    static STABLE: Cell<bool> = const { Cell::new(false) };
+}
+
+pub fn stable() -> bool {
+    STABLE.with(Cell::get)
+}
+
+// runs `f` with the backend given, and restores the previous one afterwards
+pub fn with<T>(stable: bool, f: impl FnOnce() -> T) -> T {
+    let previous = STABLE.with(|s| s.replace(stable));
+    let result = f();
+    STABLE.with(|s| s.set(previous));
+    result
+}
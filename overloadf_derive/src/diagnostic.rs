@@ -0,0 +1,68 @@
+// compiler diagnostics that still work when this crate is built on the stable toolchain.
+//
+// nightly (the `nightly` cfg, set by `build.rs`) uses `proc_macro::Diagnostic` directly. stable has no warning api, so warnings are
+// dropped there, and errors are collected and turned into `compile_error!` invocations once the
+// expansion is finished (see `take_errors`).
+#[cfg(not(nightly))]
+use std::cell::RefCell;
+
+#[cfg(not(nightly))]
+thread_local! {
+    static ERRORS: RefCell<Vec<syn::Error>> = const { RefCell::new(Vec::new()) };
+}
+
+#[cfg(nightly)]
+pub fn warning<T: Into<String>>(span: proc_macro2::Span, message: T) {
+    span.unwrap().warning(message.into()).emit();
+}
+
+#[cfg(not(nightly))]
+pub fn warning<T: Into<String>>(_span: proc_macro2::Span, _message: T) {}
+
+#[cfg(nightly)]
+pub fn error<T: Into<String>>(span: proc_macro2::Span, message: T) {
+    span.unwrap().error(message.into()).emit();
+}
+
+#[cfg(not(nightly))]
+pub fn error<T: Into<String>>(span: proc_macro2::Span, message: T) {
+    ERRORS.with(|e| e.borrow_mut().push(syn::Error::new(span, message.into())));
+}
+
+// an error together with a note pointing at a second location, e.g. the other one of two
+// conflicting overloads
+#[cfg(nightly)]
+pub fn error_with_note<T: Into<String>, N: Into<String>>(
+    span: proc_macro2::Span,
+    message: T,
+    note_span: proc_macro2::Span,
+    note: N,
+) {
+    span.unwrap()
+        .error(message.into())
+        .span_note(note_span.unwrap(), note.into())
+        .emit();
+}
+
+#[cfg(not(nightly))]
+pub fn error_with_note<T: Into<String>, N: Into<String>>(
+    span: proc_macro2::Span,
+    message: T,
+    note_span: proc_macro2::Span,
+    note: N,
+) {
+    error(span, message);
+    error(note_span, note);
+}
+
+#[cfg(nightly)]
+pub fn take_errors() -> proc_macro2::TokenStream {
+    quote!()
+}
+
+#[cfg(not(nightly))]
+pub fn take_errors() -> proc_macro2::TokenStream {
+    let errors = ERRORS.with(|e| std::mem::take(&mut *e.borrow_mut()));
+    let errors = errors.iter().map(syn::Error::to_compile_error);
+    quote!(#(#errors)*)
+}
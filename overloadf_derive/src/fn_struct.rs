@@ -104,21 +104,12 @@ fn fn_arg_typed(input: ParseStream) -> Result<PatType> {
         return Ok(pt);
     }
 
-    let id: syn::Ident = input.parse()?;
-    let pat: syn::Pat = syn::PatIdent {
-        attrs: vec![],
-        by_ref: None,
-        mutability: None,
-        ident: id,
-        subpat: None,
-    }
-    .into();
+    let pat = syn::Pat::parse_single(input)?;
     let colon_token: syn::token::Colon = input.parse()?;
     let ty: syn::Type = match input.parse::<Option<Token![...]>>()? {
         Some(dot3) => syn::Type::Verbatim(variadic_to_tokens(&dot3)),
         None => input.parse()?,
     };
-    println!("here {:?}", input);
     let assign: Option<Assign> = if input.peek(Token![=]) {
         Some(input.parse()?)
     } else {
@@ -241,7 +232,6 @@ fn attrs(outer: Vec<syn::Attribute>, inner: Vec<syn::Attribute>) -> Vec<syn::Att
 
 impl Parse for ItemFn {
     fn parse(input: ParseStream) -> Result<Self> {
-        println!("input {:?}", input);
         let outer_attrs = input.call(syn::Attribute::parse_outer)?;
         let vis: syn::Visibility = input.parse()?;
         let constness: Option<Token![const]> = input.parse()?;
@@ -254,7 +244,6 @@ impl Parse for ItemFn {
 
         let content;
         let paren_token = parenthesized!(content in input);
-        println!("content {:?}", content);
         let mut inputs = parse_fn_args(&content)?;
         let variadic = pop_variadic(&mut inputs);
 
@@ -290,6 +279,65 @@ impl Parse for ItemFn {
     }
 }
 
+// the value of a `#[default(= value)]` parameter, `Default::default()` for a bare `#[default]`
+pub fn default_value(attrs: &[syn::Attribute]) -> Option<Assign> {
+    let attr = attrs.iter().find(|attr| attr.path().is_ident("default"))?;
+    Some(match &attr.meta {
+        syn::Meta::Path(_) => syn::parse_quote!(= ::core::default::Default::default()),
+        _ => attr.parse_args().unwrap(),
+    })
+}
+
+// the arities of a `#[variadic(min = 1, max = 12)]` parameter, `min` being 0 if left out
+#[derive(Debug, Clone, Copy)]
+pub struct Variadic {
+    pub min: usize,
+    pub max: usize,
+}
+
+impl Parse for Variadic {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut min = 0;
+        let mut max = None;
+        let bounds = Punctuated::<syn::MetaNameValue, Token![,]>::parse_terminated(input)?;
+        for bound in bounds {
+            let value = match &bound.value {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Int(int),
+                    ..
+                }) => int.base10_parse()?,
+                value => return Err(syn::Error::new_spanned(value, "expected an integer")),
+            };
+            if bound.path.is_ident("min") {
+                min = value;
+            } else if bound.path.is_ident("max") {
+                max = Some(value);
+            } else {
+                return Err(syn::Error::new_spanned(
+                    bound.path,
+                    "expected `min` or `max`",
+                ));
+            }
+        }
+        let Some(max) = max else {
+            return Err(input.error("a variadic parameter needs a maximum arity: `max = 12`"));
+        };
+        Ok(Self { min, max })
+    }
+}
+
+// the arities of a `#[variadic(...)]` parameter
+pub fn variadic_arity(attrs: &[syn::Attribute]) -> Option<Result<Variadic>> {
+    let attr = attrs.iter().find(|attr| attr.path().is_ident("variadic"))?;
+    Some(match &attr.meta {
+        syn::Meta::List(_) => attr.parse_args(),
+        meta => Err(syn::Error::new_spanned(
+            meta,
+            "`variadic` takes the maximum arity: `#[variadic(max = 12)]`",
+        )),
+    })
+}
+
 impl ToTokens for Assign {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         self.eq_token.to_tokens(tokens);
@@ -408,3 +456,56 @@ impl ToTokens for ItemFn {
         });
     }
 }
+
+impl From<PatType> for syn::PatType {
+    // the default value moves into a `#[default(= ..)]` attribute, as written on `#[overload]`
+    // functions
+    fn from(arg: PatType) -> Self {
+        let mut attrs = arg.attrs;
+        if let Some(assign) = arg.assign {
+            attrs.push(syn::parse_quote!(#[default(#assign)]));
+        }
+        syn::PatType {
+            attrs,
+            pat: arg.pat,
+            colon_token: arg.colon_token,
+            ty: arg.ty,
+        }
+    }
+}
+
+impl From<ItemFn> for syn::ItemFn {
+    fn from(item: ItemFn) -> Self {
+        let sig = item.sig;
+        let inputs = sig
+            .inputs
+            .into_pairs()
+            .map(|pair| {
+                let (arg, comma) = pair.into_tuple();
+                let arg = match arg {
+                    FnArg::Receiver(receiver) => syn::FnArg::Receiver(receiver),
+                    FnArg::Typed(arg) => syn::FnArg::Typed(arg.into()),
+                };
+                Pair::new(arg, comma)
+            })
+            .collect();
+        syn::ItemFn {
+            attrs: item.attrs,
+            vis: item.vis,
+            sig: syn::Signature {
+                constness: sig.constness,
+                asyncness: sig.asyncness,
+                unsafety: sig.unsafety,
+                abi: sig.abi,
+                fn_token: sig.fn_token,
+                ident: sig.ident,
+                generics: sig.generics,
+                paren_token: sig.paren_token,
+                inputs,
+                variadic: sig.variadic,
+                output: sig.output,
+            },
+            block: item.block,
+        }
+    }
+}
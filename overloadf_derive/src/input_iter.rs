@@ -1,70 +1,119 @@
 use crate::fn_struct;
 
+// the most default parameters of one signature, and the most arities they may add up to with a
+// variadic parameter
+pub const MAX_DEFAULTS: usize = 8;
+pub const MAX_ARITIES: usize = 256;
+
+// one arity of a signature: the types it takes, the bindings of its argument tuple, the
+// statements that bind the parameters, the positions of the parameters it leaves out, and the
+// number of arguments its variadic parameter takes. the
+// parameters are bound from left to right, omitted ones to their default, so that a default can
+// use the parameters before it, but none after it. a variadic parameter is bound to an array of
+// the arguments it takes.
+pub struct Variant {
+    pub inputs: Vec<syn::Type>,
+    pub params: Vec<syn::Pat>,
+    pub bindings: Vec<proc_macro2::TokenStream>,
+    pub omitted: Vec<usize>,
+    pub arity: usize,
+}
+
+// the arities a signature takes: one for every subset of its default parameters left out, those
+// leaving out fewer first, and among them those leaving out parameters further to the right. with
+// a variadic last parameter, that for every number of arguments it takes, fewer first.
 pub struct InputIter {
-    pub index: usize,
     pub input_types: Vec<syn::Type>,
     pub param_assign: Vec<syn::Pat>,
     pub default_values: Vec<Option<fn_struct::Assign>>,
-    pub end: bool,
+    pub variadic: Option<fn_struct::Variadic>,
+    // the arguments the variadic parameter takes, with a subset of the default parameters, by
+    // bit, the rightmost parameter as the lowest bit
+    subsets: std::vec::IntoIter<(usize, u64)>,
+    defaulted: Vec<usize>,
 }
 
 impl InputIter {
-    pub fn new(a: Vec<syn::Type>, b: Vec<syn::Pat>, c: Vec<Option<fn_struct::Assign>>) -> Self {
+    pub fn new(
+        a: Vec<syn::Type>,
+        b: Vec<syn::Pat>,
+        c: Vec<Option<fn_struct::Assign>>,
+        variadic: Option<fn_struct::Variadic>,
+    ) -> Self {
+        let defaulted: Vec<usize> = (0..c.len()).rev().filter(|i| c[*i].is_some()).collect();
+        let arities = variadic.map_or(0..=0, |variadic| variadic.min..=variadic.max);
+        // signatures taking more arities than supported take none (see `param_variants`)
+        if defaulted.len() > MAX_DEFAULTS
+            || arities.clone().count() << defaulted.len() > MAX_ARITIES
+        {
+            return Self {
+                input_types: a,
+                param_assign: b,
+                default_values: c,
+                variadic,
+                subsets: vec![].into_iter(),
+                defaulted,
+            };
+        }
+        let mut subsets: Vec<u64> = (0..1_u64 << defaulted.len()).collect();
+        subsets.sort_by_key(|subset| (subset.count_ones(), *subset));
+        let subsets: Vec<(usize, u64)> = arities
+            .flat_map(|arity| subsets.iter().map(move |subset| (arity, *subset)))
+            .collect();
         Self {
-            index: a.len(),
             input_types: a,
             param_assign: b,
             default_values: c,
-            end: false,
+            variadic,
+            subsets: subsets.into_iter(),
+            defaulted,
         }
     }
 }
 
 impl Iterator for InputIter {
-    type Item = (Vec<syn::Type>, Vec<syn::Pat>, Vec<proc_macro2::TokenStream>);
+    type Item = Variant;
     fn next(&mut self) -> Option<Self::Item> {
+        let (arity, subset) = self.subsets.next()?;
+        let mut omitted: Vec<usize> = (0..self.defaulted.len())
+            .filter(|bit| subset & (1 << bit) != 0)
+            .map(|bit| self.defaulted[bit])
+            .collect();
+        omitted.sort_unstable();
         let mut inputs = vec![];
         let mut params = vec![];
-        let mut defaults = vec![];
-        if self.end {
-            return None;
-        }
-        let mut i = 0;
-        loop {
-            if i < self.index {
-                inputs.push(self.input_types[i].clone());
-                params.push(self.param_assign[i].clone());
-                i += 1;
-            } else {
-                break;
-            }
-        }
-        i = self.index;
-        loop {
-            if i < self.default_values.len() {
-                if let Some(default) = &self.default_values[i] {
-                    let p = &self.param_assign[i];
-                    defaults.push(quote!(let #p #default;));
-                } else {
-                    inputs.push(self.input_types[i].clone());
-                    params.push(self.param_assign[i].clone());
+        let mut bindings = vec![];
+        for i in 0..self.input_types.len() {
+            let p = &self.param_assign[i];
+            let ty = &self.input_types[i];
+            match &self.default_values[i] {
+                _ if self.variadic.is_some() && i + 1 == self.input_types.len() => {
+                    let args: Vec<_> = (0..arity)
+                        .map(|j| format_ident!("__overloadf_{}_{}", i, j))
+                        .collect();
+                    for arg in &args {
+                        inputs.push(ty.clone());
+                        params.push(syn::parse_quote!(#arg));
+                    }
+                    bindings.push(quote!(let #p: [#ty; #arity] = [#(#args),*];));
                 }
-                i += 1;
-            } else {
-                break;
-            }
-        }
-        loop {
-            if self.index > 0 {
-                self.index -= 1;
-                if self.default_values[self.index].is_some() {
-                    break;
+                Some(default) if omitted.contains(&i) => {
+                    bindings.push(quote!(let #p: #ty #default;))
+                }
+                _ => {
+                    let arg = format_ident!("__overloadf_{}", i);
+                    inputs.push(ty.clone());
+                    params.push(syn::parse_quote!(#arg));
+                    bindings.push(quote!(let #p = #arg;));
                 }
-            } else {
-                self.end = true;
-                break;
             }
         }
-        Some((inputs, params, defaults))
+        Some(Variant {
+            inputs,
+            params,
+            bindings,
+            omitted,
+            arity,
+        })
     }
 }
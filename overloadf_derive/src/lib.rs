@@ -1,33 +1,47 @@
-#![feature(proc_macro_diagnostic, proc_macro_span, lazy_cell)]
+#![cfg_attr(nightly, feature(proc_macro_diagnostic))]
 extern crate proc_macro;
 #[macro_use]
 extern crate syn;
 #[macro_use]
 extern crate quote;
-use core::cmp::Ordering;
 use proc_macro::TokenStream;
 use quote::ToTokens;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::sync::LazyLock;
 use std::sync::Mutex;
 use syn::parse;
-use syn::parse::Parser;
 use syn::spanned::Spanned;
+mod backend;
+mod diagnostic;
 mod fn_struct;
+use fn_struct::default_value;
 mod input_iter;
+mod options;
+mod overlap;
 
-static NAMINGS: LazyLock<Mutex<HashSet<String>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
-static TRAIT_IDENTS: LazyLock<Mutex<HashMap<String, Vec<String>>>> =
+// overload sets of free functions seen so far, by file and name. only used by the deprecated
+// `#[overload] fn`, where every overload is expanded on its own: `#[overload] mod` and
+// `overload_set!` see their whole sets at once and need no state.
+static NAMINGS: LazyLock<Mutex<HashMap<String, SetInfo>>> =
     LazyLock::new(|| Mutex::new(HashMap::new()));
-static DEFAULT_DEFINITION: LazyLock<
-    Mutex<
-        HashMap<
-            String,
-            HashMap<String, String>, // TraitItemFn
-        >,
-    >,
-> = LazyLock::new(|| Mutex::new(HashMap::new()));
 
+// what later overloads of a set are checked against: safety, whether overloads
+// differ in their return type, whether they dispatch dynamically and the backend are taken from
+// its first overload
+#[derive(Clone)]
+struct SetInfo {
+    unsafety: bool,
+    returns: bool,
+    dynamic: bool,
+    stable: bool,
+    // the trait object a `dynamic` set dispatches on, see `set_object`
+    object: Option<String>,
+    // the overloads so far, by the place of their attribute and their signature. the place
+    // alone isn't unique: every overload a `macro_rules!` macro generates has the place of its
+    // invocation. an overload expanded again (e.g. by rust-analyzer) is found by both, or by its
+    // signature if it moved, and keeps its index
+    overloads: Vec<(String, String)>,
+}
 macro_rules! quotation_expand {
     ($x: tt) => {
         if $x.is_empty() {
@@ -38,6 +52,13 @@ macro_rules! quotation_expand {
     }
 }
 
+// `call_mut` and `call` forward to `call_once`, so that the body (and the future of async
+// overloads) is only expanded once. `$opaque` is the named future of an unboxed async
+// overload (see `call_body`), which only `call_once` may define. `$constness` makes the impls
+// const, so that const overloads can be called in const contexts.
+//
+// the stable backend implements the sealed dispatch trait of the set (see
+// `dispatch_declaration`) for the argument tuple of each overload instead of the Fn* traits.
 macro_rules! fn_impl {
     (
         $impl_generics: tt,
@@ -46,32 +67,162 @@ macro_rules! fn_impl {
         $where_clause: tt,
         $output: tt,
         $attrs: tt,
-        $block: tt
+        $block: tt,
+        $opaque: tt,
+        $constness: tt
     ) => {
-        quote!(
-            impl #$impl_generics core::ops::FnOnce<#$input_types> for #$shared_type #$where_clause {
-                type Output = #$output;
-                #(#$attrs)*
-                #[inline]
-                extern "rust-call" fn call_once(self, args: #$input_types) -> Self::Output {
-                    #$block
+        if crate::backend::stable() {
+            let dispatch = format_ident!("{}_Args", $shared_type);
+            let sealed = format_ident!("{}_sealed", $shared_type);
+            quote!(
+                impl #$impl_generics #sealed::Sealed for #$input_types #$where_clause {}
+                impl #$impl_generics #dispatch for #$input_types #$where_clause {
+                    type Output = #$output;
+                    #(#$attrs)*
+                    #[inline]
+                    fn call_overload(self) -> Self::Output {
+                        let args = self;
+                        #$block
+                    }
                 }
-            }
-            impl #$impl_generics core::ops::FnMut<#$input_types> for #$shared_type #$where_clause {
-                #(#$attrs)*
-                #[inline]
-                extern "rust-call" fn call_mut(&mut self, args: #$input_types) -> Self::Output {
-                    #$block
+            )
+        } else {
+            fn_impl!(
+                @fn_traits
+                $impl_generics,
+                $input_types,
+                $shared_type,
+                $where_clause,
+                $output,
+                $attrs,
+                $block,
+                $opaque,
+                $constness
+            )
+        }
+    };
+    (
+        $impl_generics: tt,
+        $input_types: tt,
+        $shared_type: tt,
+        $where_clause: tt,
+        $output: tt,
+        $attrs: tt,
+        $block: tt,
+        $opaque: tt,
+        $constness: tt,
+        $tp: tt
+    ) => {
+        if crate::backend::stable() {
+            let dispatch = format_ident!("{}_Args", $shared_type);
+            let sealed = format_ident!("{}_sealed", $shared_type);
+            quote!(
+                impl #$impl_generics #sealed::Sealed<#$tp> for #$input_types #$where_clause {}
+                impl #$impl_generics #dispatch<#$tp> for #$input_types #$where_clause {
+                    type Output = #$output;
+                    #(#$attrs)*
+                    #[inline]
+                    fn call_overload(self) -> Self::Output {
+                        let args = self;
+                        #$block
+                    }
                 }
-            }
-            impl #$impl_generics core::ops::Fn<#$input_types> for #$shared_type #$where_clause {
-                #(#$attrs)*
-                #[inline]
-                extern "rust-call" fn call(&self, args: #$input_types) -> Self::Output {
-                    #$block
+            )
+        } else {
+            let shared_type = quote!(#$shared_type<#$tp>);
+            fn_impl!(
+                @fn_traits
+                $impl_generics,
+                $input_types,
+                shared_type,
+                $where_clause,
+                $output,
+                $attrs,
+                $block,
+                $opaque,
+                $constness
+            )
+        }
+    };
+    (
+        @fn_traits
+        $impl_generics: tt,
+        $input_types: tt,
+        $shared_type: tt,
+        $where_clause: tt,
+        $output: tt,
+        $attrs: tt,
+        $block: tt,
+        $opaque: tt,
+        $constness: tt
+    ) => {
+        {
+            let (declaration, define) = match &$opaque {
+                Some((ident, declaration)) => (declaration.clone(), quote!(#[define_opaque(#ident)])),
+                None => (quote!(), quote!()),
+            };
+            quote!(
+                #declaration
+                impl #$impl_generics #$constness core::ops::FnOnce<#$input_types> for #$shared_type #$where_clause {
+                    type Output = #$output;
+                    #(#$attrs)*
+                    #define
+                    #[inline]
+                    extern "rust-call" fn call_once(self, args: #$input_types) -> Self::Output {
+                        #$block
+                    }
                 }
-            }
-        )
+                impl #$impl_generics #$constness core::ops::FnMut<#$input_types> for #$shared_type #$where_clause {
+                    #(#$attrs)*
+                    #[inline]
+                    extern "rust-call" fn call_mut(&mut self, args: #$input_types) -> Self::Output {
+                        core::ops::FnOnce::call_once(*self, args)
+                    }
+                }
+                impl #$impl_generics #$constness core::ops::Fn<#$input_types> for #$shared_type #$where_clause {
+                    #(#$attrs)*
+                    #[inline]
+                    extern "rust-call" fn call(&self, args: #$input_types) -> Self::Output {
+                        core::ops::FnOnce::call_once(*self, args)
+                    }
+                }
+            )
+        }
+    };
+}
+
+// unsafe overloads implement `overloadf::UnsafeOverload` instead of the Fn* traits (or the
+// dispatch trait of the stable backend), so that they can only be called from unsafe code.
+macro_rules! unsafe_impl {
+    (
+        $impl_generics: tt,
+        $input_types: tt,
+        $shared_type: tt,
+        $where_clause: tt,
+        $output: tt,
+        $attrs: tt,
+        $block: tt,
+        $opaque: tt
+    ) => {
+        {
+            let (declaration, define) = match &$opaque {
+                Some((ident, declaration)) => (declaration.clone(), quote!(#[define_opaque(#ident)])),
+                None => (quote!(), quote!()),
+            };
+            quote!(
+                #declaration
+                impl #$impl_generics ::overloadf::UnsafeOverload<#$input_types> for #$shared_type #$where_clause {
+                    type Output = #$output;
+                    #(#$attrs)*
+                    #define
+                    #[inline]
+                    #[allow(unused_unsafe)]
+                    unsafe fn call_unchecked(&self, args: #$input_types) -> Self::Output {
+                        #$block
+                    }
+                }
+            )
+        }
     };
     (
         $impl_generics: tt,
@@ -81,187 +232,1645 @@ macro_rules! fn_impl {
         $output: tt,
         $attrs: tt,
         $block: tt,
+        $opaque: tt,
         $tp: tt
     ) => {
         {
             let shared_type = quote!(#$shared_type<#$tp>);
-            fn_impl!(
+            unsafe_impl!(
                 $impl_generics,
                 $input_types,
                 shared_type,
                 $where_clause,
                 $output,
                 $attrs,
-                $block
+                $block,
+                $opaque
             )
         }
     }
 }
 
-fn process_trait(mut item: syn::ItemTrait) -> TokenStream {
-    let ident = item.ident.to_string();
-    let mut map: HashMap<String, Vec<syn::TraitItemFn>> = HashMap::new();
-    let mut fn_map: HashMap<String, syn::TraitItemFn> = HashMap::new();
-    let mut items = vec![];
-    for i in &item.items {
-        let t = i.clone();
-        if let syn::TraitItem::Fn(item_method) = t {
-            let method_sig = sig_normalize(&item_method.sig);
-            map.entry(item_method.sig.ident.to_string())
-                .and_modify(|e| e.push(item_method.clone()))
-                .or_insert_with(|| vec![item_method.clone()]);
-            fn_map.insert(method_sig, item_method);
-        } else {
-            items.push(t);
-        }
-    }
-    let mut shared_fields = vec![];
-    let mut prepares = vec![];
-    for (s, i) in map.iter() {
-        match i.len().cmp(&1) {
-            Ordering::Greater => {
-                let const_field = format_ident!("{}", s);
-                shared_fields.push(const_field.to_string());
-                let shared_type = format_ident!("Overloader_{}_{}", ident, s);
-                let const_stream: TokenStream = quote!(
-                    #[allow(non_upper_case_globals)]
-                    const #const_field: #shared_type<Self> = #shared_type::<Self>(core::marker::PhantomData);
-                ).into();
-                let t = syn::parse_macro_input!(const_stream as syn::TraitItemConst);
-                items.push(syn::TraitItem::Const(t));
-                let vis = item.vis.clone();
-                let prepare = quote!(
-                    #[doc(hidden)]
-                    #[allow(non_camel_case_types)]
-                    #[allow(dead_code)]
-                    #[derive(Copy, Clone)]
-                    #vis struct #shared_type<S>(core::marker::PhantomData<S>);
-                    unsafe impl<S> Send for #shared_type<S> {}
-                    unsafe impl<S> Sync for #shared_type<S> {}
-                );
-                prepares.push(prepare);
-            }
-            Ordering::Equal => {
-                items.push(syn::TraitItem::Fn(i[0].clone()));
-            }
-            Ordering::Less => {}
+// free function overloads with `returns` implement `overloadf::ReturnOverload` instead of the Fn*
+// traits (or the dispatch trait of the stable backend). the return type is a parameter of the
+// trait, so overloads may differ in it only, and a type parameter may appear in it only.
+macro_rules! return_impl {
+    (
+        $impl_generics: tt,
+        $input_types: tt,
+        $shared_type: tt,
+        $where_clause: tt,
+        $output: tt,
+        $attrs: tt,
+        $block: tt,
+        $opaque: tt
+    ) => {
+        {
+            let (declaration, define) = match &$opaque {
+                Some((ident, declaration)) => (declaration.clone(), quote!(#[define_opaque(#ident)])),
+                None => (quote!(), quote!()),
+            };
+            quote!(
+                #declaration
+                impl #$impl_generics ::overloadf::ReturnOverload<#$input_types, #$output> for #$shared_type #$where_clause {
+                    #(#$attrs)*
+                    #define
+                    #[inline]
+                    fn call_returning(&self, args: #$input_types) -> #$output {
+                        #$block
+                    }
+                }
+            )
         }
+    };
+}
+
+// `const` for the Fn* impls of a const overload. the stable backend has no const dispatch and
+// `UnsafeOverload` is not a const trait, so const is dropped for those.
+fn const_impl(sig: &syn::Signature) -> Option<Token![const]> {
+    let constness = sig.constness?;
+    if backend::stable() || sig.unsafety.is_some() {
+        diagnostic::warning(
+            constness.span,
+            "const is only supported for safe overloads on nightly. ".to_owned()
+                + "Will ignore const to produce workable functions",
+        );
+        None
+    } else {
+        Some(constness)
     }
-    DEFAULT_DEFINITION.lock().unwrap().insert(
-        ident.clone(),
-        fn_map
-            .into_iter()
-            .map(|(k, v)| (k, v.into_token_stream().to_string()))
-            .collect(),
+}
+
+// error for an overload whose safety differs from the rest of its set
+fn mixed_safety(sig: &syn::Signature) {
+    diagnostic::error(
+        sig.span(),
+        format!(
+            "overload set `{}` mixes safe and unsafe overloads. unsafe overloads are only \
+             callable through `UnsafeOverload::call_unchecked`, put them in a separate set",
+            sig.ident
+        ),
     );
-    TRAIT_IDENTS.lock().unwrap().insert(ident, shared_fields);
-    item.items = items;
-    let result = quote!(
-        #(#prepares)*
-        #item
+}
+
+// error for an overload whose `returns` option differs from the rest of its set
+fn mixed_returns(sig: &syn::Signature) {
+    diagnostic::error(
+        sig.span(),
+        format!(
+            "overload set `{}` mixes overloads with and without `returns`. put \
+             `#[overload(returns)]` on every overload of the set",
+            sig.ident
+        ),
     );
-    result.into()
 }
 
-fn replace_self<F: ToTokens, T: ToTokens, O: syn::parse::Parse>(
-    input: F,
-    to: T,
-) -> syn::parse::Result<O> {
-    let input_str = input
-        .to_token_stream()
-        .to_string()
-        .replace("Self", &to.to_token_stream().to_string())
-        .replace("self", "__self");
-    syn::parse_str(&input_str)
+// error for an overload whose `dynamic` option differs from the rest of its set
+fn mixed_dynamic(sig: &syn::Signature) {
+    diagnostic::error(
+        sig.span(),
+        format!(
+            "overload set `{}` mixes overloads with and without `dynamic`. put \
+             `#[overload(dynamic)]` on every overload of the set",
+            sig.ident
+        ),
+    );
 }
 
-fn get_output(ast: &syn::ReturnType, tp: &syn::Type) -> proc_macro2::TokenStream {
-    let new_output: syn::ReturnType = replace_self(ast, tp).unwrap();
-    match new_output {
-        syn::ReturnType::Default => quote!(()),
-        syn::ReturnType::Type(_, t) => quote!(#t),
+// error for an overload whose `stable` option differs from the rest of its set: the set is
+// either called through the Fn* traits or through `call`
+fn mixed_backends(sig: &syn::Signature) {
+    diagnostic::error(
+        sig.span(),
+        format!(
+            "overload set `{}` mixes overloads with and without `stable`. put \
+             `#[overload(stable)]` on every overload of the set",
+            sig.ident
+        ),
+    );
+}
+
+// error for an overload of a `dynamic` set returning another type than the first one: a call
+// may end up in any overload of the set
+fn dynamic_output(sig: &syn::Signature, first: &syn::Signature) {
+    let output = |sig: &syn::Signature| match &sig.output {
+        syn::ReturnType::Default => "()".to_string(),
+        syn::ReturnType::Type(_, ty) => token_string(ty),
+    };
+    if output(sig) != output(first) {
+        diagnostic::error(
+            sig.output.span(),
+            format!(
+                "overloads of `{}` are dispatched dynamically, so they have to return the same \
+                 type. the first one returns `{}`",
+                sig.ident,
+                output(first)
+            ),
+        );
     }
 }
 
-struct IdentGen(pub u32);
-impl IdentGen {
-    #[inline]
-    pub fn new() -> Self {
-        Self(0)
+// declarations the stable backend needs next to the overloader struct: the sealed dispatch trait
+// and the generic `call` entry point. the module of `Sealed` stays private, so that only the
+// impls generated next to it can name it. `generics` are those of overloaders of methods, which
+// carry the parameters of the trait or impl (see `overloader_generics`).
+fn dispatch_declaration(
+    vis: &syn::Visibility,
+    shared_type: &syn::Ident,
+    generics: Option<&syn::Generics>,
+) -> proc_macro2::TokenStream {
+    if !backend::stable() {
+        return quote!();
     }
-    #[inline]
-    pub fn ident(&mut self) -> syn::Ident {
-        self.0 += 1;
-        syn::Ident::new(&format!("_{}", self.0), proc_macro2::Span::call_site())
+    let dispatch = format_ident!("{}_Args", shared_type);
+    let sealed = format_ident!("{}_sealed", shared_type);
+    if let Some(generics) = generics {
+        let (impl_generics, ty_generics, _) = generics.split_for_impl();
+        quote!(
+            #[doc(hidden)]
+            #[allow(non_snake_case)]
+            mod #sealed {
+                pub trait Sealed #impl_generics {}
+            }
+            #[doc(hidden)]
+            #[allow(non_camel_case_types)]
+            #vis trait #dispatch #impl_generics: #sealed::Sealed #ty_generics {
+                type Output;
+                fn call_overload(self) -> Self::Output;
+            }
+            impl #impl_generics #shared_type #ty_generics {
+                #[inline]
+                #[allow(dead_code)]
+                #vis fn call<A: #dispatch #ty_generics>(self, args: A) -> A::Output {
+                    args.call_overload()
+                }
+            }
+        )
+    } else {
+        quote!(
+            #[doc(hidden)]
+            #[allow(non_snake_case)]
+            mod #sealed {
+                pub trait Sealed {}
+            }
+            #[doc(hidden)]
+            #[allow(non_camel_case_types)]
+            #vis trait #dispatch: #sealed::Sealed {
+                type Output;
+                fn call_overload(self) -> Self::Output;
+            }
+            impl #shared_type {
+                #[inline]
+                #[allow(dead_code)]
+                #vis fn call<A: #dispatch>(self, args: A) -> A::Output {
+                    args.call_overload()
+                }
+            }
+        )
     }
-    #[inline]
-    pub fn id(&self, id: u32) -> syn::Ident {
-        syn::Ident::new(&format!("_{}", id), proc_macro2::Span::call_site())
+}
+
+// `xdd!(a, b)` for the overload set `xdd`. it packs its arguments into a tuple for the set: nightly
+// calls the set directly, the stable backend through its `call` entry point, and sets with
+// `returns` go through `ReturnOverload` on both. with `macro = crate::geometry`, it names the set
+// through `$crate`, so it works from any module and crate; otherwise the set has to be in scope
+// where the macro is called. `direct` are the const fns of the overloads, by arity (see
+// `fn_overload`): an arity only one overload takes, which is const, is called directly, so that
+// the macro works in const contexts.
+//
+// the macro has a name of its own, so that sets of the same name don't clash, and is re-exported
+// next to the set by the name of the set, with its visibility: importing `xdd` brings in both the
+// set and its macro. a public set exports it at the crate root, like the macro of a trait (see
+// `trait_macro`).
+fn call_macro(
+    vis: &syn::Visibility,
+    ident: &syn::Ident,
+    options: &options::Options,
+    returns: bool,
+    direct: &[(usize, Option<syn::Ident>)],
+) -> proc_macro2::TokenStream {
+    let (set, check) = match &options.macro_path {
+        Some(path) => {
+            let module = path.segments.iter().skip(1);
+            (
+                quote!($crate #(::#module)* :: #ident),
+                quote!(
+                    const _: () = {
+                        let _ = #path :: #ident;
+                    };
+                ),
+            )
+        }
+        None => (quote!(#ident), quote!()),
+    };
+    let body = if returns {
+        quote!(::overloadf::ReturnOverload::call_returning(&#set, ($($arg,)*)))
+    } else if backend::stable() {
+        quote!(#set.call(($($arg,)*)))
+    } else {
+        quote!(#set($($arg),*))
+    };
+    let arms = direct.iter().filter_map(|(arity, name)| {
+        let name = name.as_ref()?;
+        if direct.iter().filter(|(other, _)| other == arity).count() > 1 {
+            return None;
+        }
+        let args: Vec<_> = (0..*arity).map(|i| format_ident!("a{}", i)).collect();
+        Some(if args.is_empty() {
+            quote!(() => { #set.#name(()) };)
+        } else {
+            quote!((#($#args:expr),* $(,)?) => { #set.#name((#($#args,)*)) };)
+        })
+    });
+    let rules = quote!(
+        #(#arms)*
+        ($($arg:expr),* $(,)?) => {
+            #body
+        };
+    );
+    use std::hash::{Hash, Hasher};
+    let site = proc_macro::Span::call_site();
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    (site.file(), site.line(), site.column()).hash(&mut hasher);
+    let hidden = format_ident!("__overloadf_macro_{}_{:x}", ident, hasher.finish());
+    let export = match vis {
+        syn::Visibility::Public(_) => quote!(#[macro_export]),
+        _ => quote!(#[allow(unused_macros)]),
+    };
+    quote!(
+        #check
+        #[doc(hidden)]
+        #export
+        macro_rules! #hidden {
+            #rules
+        }
+        #[allow(unused_imports)]
+        #vis use #hidden as #ident;
+    )
+}
+
+// invoke the overload set `set` with the argument tuple `args`
+fn dispatch_call(
+    set: proc_macro2::TokenStream,
+    args: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    if backend::stable() {
+        quote!(#set.call(#args))
+    } else {
+        quote!(core::ops::FnOnce::call_once(#set, #args))
     }
 }
 
-fn generics_normalize(ast: &syn::Generics) -> syn::Generics {
-    let mut gen = ast.clone();
-    let mut matching_table = HashMap::new();
-    let mut idgen = IdentGen::new();
-    for gen_param in gen.params.iter_mut() {
-        match gen_param {
-            syn::GenericParam::Type(t) => {
-                matching_table
-                    .entry(t.ident.to_string())
-                    .and_modify(|e| {
-                        t.ident = idgen.id(*e);
-                    })
-                    .or_insert_with(|| {
-                        t.ident = idgen.ident();
-                        idgen.0
-                    });
-            }
-            syn::GenericParam::Lifetime(l) => {
-                matching_table
-                    .entry(l.lifetime.ident.to_string())
-                    .and_modify(|e| {
-                        l.lifetime.ident = idgen.id(*e);
-                    })
-                    .or_insert_with(|| {
-                        l.lifetime.ident = idgen.ident();
-                        idgen.0
-                    });
-                for ll in l.bounds.iter_mut() {
-                    matching_table
-                        .entry(ll.ident.to_string())
-                        .and_modify(|e| {
-                            ll.ident = idgen.id(*e);
-                        })
-                        .or_insert_with(|| {
-                            ll.ident = idgen.ident();
-                            idgen.0
-                        });
+// overloaded methods become associated consts, so method call syntax is given back through an
+// extension trait per method name, implemented for the receiver type of every overload. when
+// all overloads take the same number of arguments after the receiver, its method takes them
+// like the overloads do. a method can't take a varying number of arguments, so otherwise it
+// takes them as one value: `()`, a single value, or a tuple. for trait methods the trait is also
+// generic over the overloader, which carries the parameters of the trait.
+struct MethodExt {
+    ident: syn::Ident,
+    // the number of arguments every overload takes after the receiver
+    arity: Option<usize>,
+}
+
+impl MethodExt {
+    fn new<'a, I: IntoIterator<Item = &'a syn::Signature>>(ident: syn::Ident, sigs: I) -> Self {
+        let mut arities = BTreeSet::new();
+        for sig in sigs.into_iter().filter(|sig| has_receiver([*sig])) {
+            let mut required = 0;
+            let mut defaults = 0;
+            let mut variadic = 0..=0;
+            for input in sig.inputs.iter().skip(1) {
+                let syn::FnArg::Typed(tp) = input else {
+                    continue;
+                };
+                if let Some(fn_struct::Variadic { min, max }) =
+                    fn_struct::variadic_arity(&tp.attrs).and_then(Result::ok)
+                {
+                    variadic = min..=max;
+                } else if default_value(&tp.attrs).is_some() {
+                    defaults += 1;
+                } else {
+                    required += 1;
                 }
             }
-            syn::GenericParam::Const(_) => {
-                // left untouched, should not have any renaming here
+            for count in variadic {
+                arities.extend((0..=defaults).map(|given| required + given + count));
             }
         }
+        let arity = match arities.len() {
+            1 => arities.pop_first(),
+            _ => None,
+        };
+        Self { ident, arity }
     }
-    if let Some(mut wh) = gen.where_clause.clone() {
-        let mut p = syn::punctuated::Punctuated::<_, syn::Token![,]>::new();
-        for predicate in wh.predicates.iter_mut() {
-            let mut take = true;
-            match predicate {
-                syn::WherePredicate::Type(t) => {
-                    if let Some(l) = &mut t.lifetimes {
-                        for ll in l.lifetimes.iter_mut() {
-                            if let syn::GenericParam::Lifetime(lp) = ll {
-                                if let Some(v) = matching_table.get(&lp.lifetime.ident.to_string())
-                                {
-                                    lp.lifetime.ident = idgen.id(*v);
-                                    for lll in lp.bounds.iter_mut() {
-                                        if let Some(v) = matching_table.get(&lll.ident.to_string())
-                                        {
-                                            lll.ident = idgen.id(*v);
-                                        } else {
-                                            take = false;
+}
+
+fn method_ext_declaration(
+    vis: &syn::Visibility,
+    ext: &MethodExt,
+    method: &syn::Ident,
+    trait_method: bool,
+) -> proc_macro2::TokenStream {
+    let set = if trait_method {
+        Some(format_ident!("Set"))
+    } else {
+        None
+    };
+    let (params, args) = match ext.arity {
+        Some(arity) => (
+            (0..arity).map(|i| format_ident!("A{}", i)).collect(),
+            (0..arity).map(|i| format_ident!("_{}", i)).collect(),
+        ),
+        None => (vec![format_ident!("Args")], vec![format_ident!("args")]),
+    };
+    let ident = &ext.ident;
+    let generics = params.iter().chain(&set);
+    quote!(
+        #[allow(non_camel_case_types)]
+        #vis trait #ident<#(#generics),*> {
+            type Output;
+            fn #method(self, #(#args: #params),*) -> Self::Output;
+        }
+    )
+}
+
+fn method_ext_impl(
+    ext: &MethodExt,
+    method: &syn::Ident,
+    set: proc_macro2::TokenStream,
+    generics: &syn::Generics,
+    input_types: &[syn::Type],
+    output: &proc_macro2::TokenStream,
+    overloader: Option<&proc_macro2::TokenStream>,
+) -> proc_macro2::TokenStream {
+    let (impl_generics, _ty_generics, where_clause) = generics.split_for_impl();
+    let (receiver, args) = match input_types.split_first() {
+        Some(split) => split,
+        None => return quote!(),
+    };
+    let ids: Vec<_> = (0..args.len()).map(|i| format_ident!("_{}", i)).collect();
+    let call = dispatch_call(set, quote!((self, #(#ids),*)));
+    let (params, mut trait_args) = match (ext.arity, args) {
+        (Some(_), _) => (
+            quote!(#(#ids: #args),*),
+            args.iter().map(ToTokens::to_token_stream).collect(),
+        ),
+        (None, []) => (quote!(_: ()), vec![quote!(())]),
+        (None, [arg]) => (quote!(_0: #arg), vec![quote!(#arg)]),
+        (None, _) => (
+            quote!((#(#ids),*): (#(#args),*)),
+            vec![quote!((#(#args),*))],
+        ),
+    };
+    trait_args.extend(overloader.cloned());
+    let ident = &ext.ident;
+    quote!(
+        impl #impl_generics #ident<#(#trait_args),*> for #receiver #where_clause {
+            type Output = #output;
+            #[inline]
+            fn #method(self, #params) -> Self::Output {
+                #call
+            }
+        }
+    )
+}
+
+fn has_receiver<'a, I: IntoIterator<Item = &'a syn::Signature>>(sigs: I) -> bool {
+    sigs.into_iter()
+        .any(|sig| matches!(sig.inputs.first(), Some(syn::FnArg::Receiver(_))))
+}
+
+// the trait method behind overload `index` of method `ident`. trait and impl expansion derive it
+// the same way, so an impl only has to know the position of each overload in the trait.
+fn hidden_method(ident: &syn::Ident, index: usize) -> syn::Ident {
+    use syn::ext::IdentExt;
+    syn::Ident::new(
+        &format!("__overloadf_{}_{}", ident.unraw(), index),
+        ident.span(),
+    )
+}
+
+// `Self` of a signature, as seen from the overloader impls: `Self::Item` becomes `qualified::Item`
+// (`<OverloadfSelf as Trait>::Item` for traits), any other `Self` becomes `this`
+fn self_tokens<T: ToTokens>(
+    input: proc_macro2::TokenStream,
+    this: &T,
+    qualified: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let mut output = proc_macro2::TokenStream::new();
+    let mut trees = input.into_iter().peekable();
+    while let Some(tree) = trees.next() {
+        match tree {
+            proc_macro2::TokenTree::Ident(ident) if ident == "Self" => match trees.peek() {
+                Some(proc_macro2::TokenTree::Punct(p)) if p.as_char() == ':' => {
+                    output.extend(qualified.clone())
+                }
+                _ => output.extend(quote!(#this)),
+            },
+            proc_macro2::TokenTree::Group(group) => {
+                let mut replaced = proc_macro2::Group::new(
+                    group.delimiter(),
+                    self_tokens(group.stream(), this, qualified),
+                );
+                replaced.set_span(group.span());
+                output.extend(Some(proc_macro2::TokenTree::Group(replaced)));
+            }
+            tree => output.extend(Some(tree)),
+        }
+    }
+    output
+}
+
+// `self` becomes `__self`, the binding of the receiver in the argument tuple
+fn rename_self(input: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    input
+        .into_iter()
+        .map(|tree| match tree {
+            proc_macro2::TokenTree::Ident(ident) if ident == "self" => {
+                proc_macro2::TokenTree::Ident(syn::Ident::new("__self", ident.span()))
+            }
+            proc_macro2::TokenTree::Group(group) => {
+                let mut replaced =
+                    proc_macro2::Group::new(group.delimiter(), rename_self(group.stream()));
+                replaced.set_span(group.span());
+                proc_macro2::TokenTree::Group(replaced)
+            }
+            tree => tree,
+        })
+        .collect()
+}
+
+// what the overloader impls of all overloaded methods of a trait share
+struct TraitContext {
+    // the implementing type, a type parameter of every overloader
+    this: syn::Ident,
+    // the trait with its parameters, `Trait<T>`
+    path: proc_macro2::TokenStream,
+    // `<OverloadfSelf as Trait<T>>`
+    qualified: proc_macro2::TokenStream,
+    // the generics of the trait, with `Self` replaced
+    generics: syn::Generics,
+    // the arguments of an overloader: the implementing type and the parameters of the trait
+    overloader_args: proc_macro2::TokenStream,
+}
+
+impl TraitContext {
+    fn new(item: &syn::ItemTrait) -> Self {
+        let this = format_ident!("OverloadfSelf");
+        let ident = &item.ident;
+        let (_, ty_generics, _) = item.generics.split_for_impl();
+        let path = quote!(#ident #ty_generics);
+        let qualified = quote!(<#this as #path>);
+        let overloader_args = generic_args(&overloader_generics(&item.generics, Some(&this)));
+        let generics = self_generics(&item.generics, &this, &qualified);
+        Self {
+            this,
+            path,
+            qualified,
+            generics,
+            overloader_args: quote!(#(#overloader_args),*),
+        }
+    }
+
+    fn replace_self<T: ToTokens, O: syn::parse::Parse>(&self, input: T) -> O {
+        syn::parse2(self_tokens(
+            input.to_token_stream(),
+            &self.this,
+            &self.qualified,
+        ))
+        .unwrap()
+    }
+}
+
+fn self_generics(
+    generics: &syn::Generics,
+    this: &syn::Ident,
+    qualified: &proc_macro2::TokenStream,
+) -> syn::Generics {
+    let mut replaced: syn::Generics =
+        syn::parse2(self_tokens(generics.to_token_stream(), this, qualified)).unwrap();
+    replaced.where_clause = generics.where_clause.as_ref().map(|where_clause| {
+        syn::parse2(self_tokens(where_clause.to_token_stream(), this, qualified)).unwrap()
+    });
+    replaced
+}
+
+// generics of an overloader: the parameters of the trait or impl block, without bounds, and for
+// trait methods the implementing type `this`. lifetimes go first.
+fn overloader_generics(generics: &syn::Generics, this: Option<&syn::Ident>) -> syn::Generics {
+    let lifetimes = generics.lifetimes().map(|l| &l.lifetime);
+    let this = this.iter();
+    let types = generics.type_params().map(|t| &t.ident);
+    let consts = generics.const_params().map(|c| {
+        let (ident, ty) = (&c.ident, &c.ty);
+        quote!(const #ident: #ty)
+    });
+    parse_quote!(<#(#lifetimes,)* #(#this,)* #(#types,)* #(#consts),*>)
+}
+
+// the overloader type of a set, with the parameters of `generics` (see `overloader_generics`)
+fn overloader_declaration(
+    vis: &syn::Visibility,
+    shared_type: &syn::Ident,
+    generics: &syn::Generics,
+) -> proc_macro2::TokenStream {
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    let phantoms = generics.params.iter().filter_map(|param| match param {
+        syn::GenericParam::Lifetime(l) => {
+            let lifetime = &l.lifetime;
+            Some(quote!(&#lifetime ()))
+        }
+        syn::GenericParam::Type(t) => Some(t.ident.to_token_stream()),
+        syn::GenericParam::Const(_) => None,
+    });
+    quote!(
+        #[doc(hidden)]
+        #[allow(non_camel_case_types)]
+        #[allow(dead_code)]
+        #vis struct #shared_type #impl_generics (core::marker::PhantomData<(#(#phantoms,)*)>);
+        // not derived: that would require the parameters to be `Copy`
+        impl #impl_generics Clone for #shared_type #ty_generics {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+        impl #impl_generics Copy for #shared_type #ty_generics {}
+        unsafe impl #impl_generics Send for #shared_type #ty_generics {}
+        unsafe impl #impl_generics Sync for #shared_type #ty_generics {}
+    )
+}
+
+// tokens as reflection shows them, without the spaces `to_string` puts between all of them:
+// `Vec<&'a str>` rather than `Vec < & 'a str >`
+fn token_string<T: ToTokens>(tokens: &T) -> String {
+    let spaced = tokens.to_token_stream().to_string();
+    let mut text = String::new();
+    let mut chars = spaced.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == ' ' {
+            let before = text.chars().last().unwrap_or(' ');
+            let after = chars.peek().copied().unwrap_or(' ');
+            let glued_after =
+                matches!(before, '&' | '<' | '(' | '[' | '.' | '#' | '!') || text.ends_with("::");
+            let glued_before = matches!(after, ',' | ';' | '>' | ')' | ']' | ':' | '.' | '?')
+                || (after == '<' && before != '=')
+                || (matches!(after, '(' | '[' | '!')
+                    && (before.is_alphanumeric() || matches!(before, '_' | '>')));
+            if glued_after || glued_before {
+                continue;
+            }
+        }
+        text.push(c);
+    }
+    text
+}
+
+// an overload as its documentation shows it, like `OverloadInfo` displays it:
+// `fn xdd<T: Debug>(a: T, b: u64 = 32) -> u64`
+fn signature_doc(sig: &syn::Signature) -> String {
+    let params: Vec<String> = sig
+        .inputs
+        .iter()
+        .map(|input| match input {
+            syn::FnArg::Receiver(r) => {
+                let mut r = r.clone();
+                r.attrs.clear();
+                token_string(&r)
+            }
+            syn::FnArg::Typed(tp) => {
+                let pat = token_string(&tp.pat);
+                let ty = token_string(&tp.ty);
+                let param = match fn_struct::variadic_arity(&tp.attrs).and_then(Result::ok) {
+                    Some(fn_struct::Variadic { min, max }) => {
+                        format!("{}: [{}; {}..={}]", pat, ty, min, max)
+                    }
+                    None => format!("{}: {}", pat, ty),
+                };
+                match default_value(&tp.attrs) {
+                    Some(assign) => format!("{} = {}", param, token_string(&assign.right)),
+                    None => param,
+                }
+            }
+        })
+        .collect();
+    let mut doc = format!(
+        "{}{}{}fn {}{}({})",
+        if sig.constness.is_some() {
+            "const "
+        } else {
+            ""
+        },
+        if sig.asyncness.is_some() {
+            "async "
+        } else {
+            ""
+        },
+        if sig.unsafety.is_some() {
+            "unsafe "
+        } else {
+            ""
+        },
+        sig.ident,
+        token_string(&sig.generics),
+        params.join(", ")
+    );
+    if let syn::ReturnType::Type(_, ty) = &sig.output {
+        doc.push_str(&format!(" -> {}", token_string(ty)));
+    }
+    if let Some(where_clause) = &sig.generics.where_clause {
+        doc.push_str(&format!(" {}", where_string(where_clause)));
+    }
+    doc
+}
+
+// `where T: Copy`, without a trailing comma
+fn where_string(where_clause: &syn::WhereClause) -> String {
+    let mut where_clause = where_clause.clone();
+    where_clause.predicates.pop_punct();
+    token_string(&where_clause)
+}
+
+// the `///` docs of an item, by line and without their common indentation
+fn doc_lines(attrs: &[syn::Attribute]) -> Vec<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(nv) if nv.path.is_ident("doc") => match &nv.value {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(s),
+                    ..
+                }) => Some(s.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .flat_map(|doc| doc.lines().map(str::to_string).collect::<Vec<_>>())
+        .collect();
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or("").trim_end().to_string())
+        .collect()
+}
+
+// an overload as an item of the list in the documentation of its set: the signature, then its
+// own docs indented to stay in the item
+fn overload_doc(sig: &syn::Signature, attrs: &[syn::Attribute]) -> String {
+    let mut doc = format!("- `{}`\n", signature_doc(sig));
+    let lines = doc_lines(attrs);
+    if !lines.is_empty() {
+        doc.push('\n');
+    }
+    for line in lines {
+        if line.is_empty() {
+            doc.push('\n');
+        } else {
+            doc.push_str(&format!("  {}\n", line));
+        }
+    }
+    doc
+}
+
+// an empty impl of a free function overloader documented with one of its overloads, for rustdoc
+// only. the overloads of `#[overload] fn` are expanded one at a time, so only the rustdoc page of
+// the overloader lists all of them; hover in an editor shows the value, with the first one.
+fn overload_impl_doc(
+    shared_type: &syn::Ident,
+    sig: &syn::Signature,
+    attrs: &[syn::Attribute],
+) -> proc_macro2::TokenStream {
+    let mut doc = format!("`{}`\n", signature_doc(sig));
+    let lines = doc_lines(attrs);
+    if !lines.is_empty() {
+        doc.push_str(&format!("\n{}\n", lines.join("\n")));
+    }
+    quote!(
+        #[cfg(doc)]
+        #[doc = #doc]
+        impl #shared_type {}
+    )
+}
+
+// `#[doc]` of the value of a set, listing its overloads
+fn set_docs<'a, I: IntoIterator<Item = (&'a syn::Signature, &'a [syn::Attribute])>>(
+    intro: &str,
+    overloads: I,
+) -> proc_macro2::TokenStream {
+    let items: Vec<String> = overloads
+        .into_iter()
+        .map(|(sig, attrs)| overload_doc(sig, attrs))
+        .collect();
+    let doc = format!("{}\n\n{}", intro, items.join("\n"));
+    quote!(#[doc = #doc])
+}
+
+// the `overloadf::OverloadInfo` of an overload
+fn overload_info(sig: &syn::Signature) -> proc_macro2::TokenStream {
+    let name = sig.ident.to_string();
+    let params = sig.inputs.iter().map(|input| {
+        let (name, ty, default, variadic) = match input {
+            syn::FnArg::Receiver(r) => ("self".to_string(), token_string(&r.ty), None, None),
+            syn::FnArg::Typed(tp) => (
+                token_string(&tp.pat),
+                token_string(&tp.ty),
+                default_value(&tp.attrs).map(|assign| token_string(&assign.right)),
+                fn_struct::variadic_arity(&tp.attrs).and_then(Result::ok),
+            ),
+        };
+        let default = match default {
+            Some(default) => quote!(Some(#default)),
+            None => quote!(None),
+        };
+        let variadic = match variadic {
+            Some(fn_struct::Variadic { min, max }) => quote!(Some((#min, #max))),
+            None => quote!(None),
+        };
+        quote!(::overloadf::ParamInfo {
+            name: #name,
+            ty: #ty,
+            default: #default,
+            variadic: #variadic,
+        })
+    });
+    let output = match &sig.output {
+        syn::ReturnType::Default => "()".to_string(),
+        syn::ReturnType::Type(_, ty) => token_string(ty),
+    };
+    let generics = token_string(&sig.generics);
+    let where_clause = sig
+        .generics
+        .where_clause
+        .as_ref()
+        .map_or(String::new(), where_string);
+    let is_async = sig.asyncness.is_some();
+    let is_unsafe = sig.unsafety.is_some();
+    quote!(::overloadf::OverloadInfo {
+        name: #name,
+        generics: #generics,
+        params: &[#(#params),*],
+        output: #output,
+        where_clause: #where_clause,
+        is_async: #is_async,
+        is_unsafe: #is_unsafe,
+    })
+}
+
+// `OverloadSet` and `Debug` of an overloader. `lookup` is the body of `overload`, which finds
+// the overload at `index` (see `listed_overloads` and `probed_overloads`).
+fn set_reflection(
+    shared_type: &syn::Ident,
+    generics: &syn::Generics,
+    name: &str,
+    lookup: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote!(
+        impl #impl_generics ::overloadf::OverloadSet for #shared_type #ty_generics #where_clause {
+            fn name(&self) -> &'static str {
+                #name
+            }
+            fn overload(&self, __overloadf_index: usize) -> Option<::overloadf::OverloadInfo> {
+                #lookup
+            }
+        }
+        impl #impl_generics core::fmt::Debug for #shared_type #ty_generics #where_clause {
+            fn fmt(&self, __overloadf_f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                ::overloadf::__fmt_set(self, __overloadf_f)
+            }
+        }
+    )
+}
+
+// `overload` of a set whose overloads are all known
+fn listed_overloads<'a, I: IntoIterator<Item = &'a syn::Signature>>(
+    sigs: I,
+) -> proc_macro2::TokenStream {
+    let infos = sigs.into_iter().map(overload_info);
+    quote!(
+        const OVERLOADS: &[::overloadf::OverloadInfo] = &[#(#infos),*];
+        OVERLOADS.get(__overloadf_index).copied()
+    )
+}
+
+// `overload` of a free function set, whose overloads are expanded one at a time. the first
+// one is probed for, and each finds the next (see `overload_entry`)
+fn probed_overloads(shared_type: &syn::Ident) -> proc_macro2::TokenStream {
+    quote!(
+        #[allow(unused_imports)]
+        use ::overloadf::{__Found as _, __Missing as _};
+        (&&::overloadf::__Probe::<#shared_type, 0>(self)).__nth(__overloadf_index)
+    )
+}
+
+// a free function overload at `index` of its set, for `probed_overloads` and
+// `probed_dyn_calls`. `dyn_call` is its part of `__call_dyn` (see `dyn_call`). without `sig`,
+// the overload conflicts with another one and was left out: the entry only passes on to the
+// next one, so that the overloads after it are still found.
+fn overload_entry(
+    shared_type: &syn::Ident,
+    index: usize,
+    sig: Option<&syn::Signature>,
+    dyn_call: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let next = index + 1;
+    let next = quote!((&&::overloadf::__Probe::<#shared_type, #next>(self)));
+    let nth = match sig {
+        Some(sig) => {
+            let info = overload_info(sig);
+            quote!(
+                match __overloadf_n.checked_sub(1) {
+                    None => Some(#info),
+                    Some(__overloadf_n) => #next.__nth(__overloadf_n),
+                }
+            )
+        }
+        None => quote!(#next.__nth(__overloadf_n)),
+    };
+    quote!(
+        impl ::overloadf::__OverloadEntry<#index> for #shared_type {
+            fn __nth(&self, __overloadf_n: usize) -> Option<::overloadf::OverloadInfo> {
+                #[allow(unused_imports)]
+                use ::overloadf::{__Found as _, __Missing as _};
+                #nth
+            }
+            fn __call_dyn(
+                &self,
+                __overloadf_args: &mut ::overloadf::DynArgs,
+            ) -> ::overloadf::__DynResult {
+                #[allow(unused_imports)]
+                use ::overloadf::{__Found as _, __Missing as _};
+                #dyn_call
+                #next.__call_dyn(__overloadf_args)
+            }
+        }
+    )
+}
+
+// `DynOverload` of an overloader, with `body` as `__call_dyn`. `generics` are those of the impl
+// the set comes from, with their bounds, and `overloader` those of the overloader. calls go
+// through `Any`, so the parameters have to be `'static`.
+fn set_dynamic(
+    shared_type: &syn::Ident,
+    generics: &syn::Generics,
+    overloader: &syn::Generics,
+    body: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let mut generics = merge_generics(None, generics, &syn::Generics::default());
+    for param in generics.params.iter_mut() {
+        match param {
+            syn::GenericParam::Type(t) => t.bounds.push(parse_quote!('static)),
+            syn::GenericParam::Lifetime(l) => l.bounds.push(parse_quote!('static)),
+            syn::GenericParam::Const(_) => {}
+        }
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = overloader.split_for_impl();
+    quote!(
+        impl #impl_generics ::overloadf::DynOverload for #shared_type #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn __call_dyn(
+                &self,
+                __overloadf_args: &mut ::overloadf::DynArgs,
+            ) -> ::overloadf::__DynResult {
+                #body
+            }
+        }
+    )
+}
+
+// `__call_dyn` of a free function set, trying the overloads one after another from the first
+// one, like `probed_overloads` finds them
+fn probed_dyn_calls(shared_type: &syn::Ident) -> proc_macro2::TokenStream {
+    quote!(
+        #[allow(unused_imports)]
+        use ::overloadf::{__Found as _, __Missing as _};
+        (&&::overloadf::__Probe::<#shared_type, 0>(self)).__call_dyn(__overloadf_args)
+    )
+}
+
+// whether `DynOverload` can call an overload: its types have to be named to be told apart
+// through `Any`
+fn dyn_callable(sig: &syn::Signature) -> bool {
+    let impl_trait = format_ident!("impl");
+    sig.generics.type_params().next().is_none()
+        && sig.generics.const_params().next().is_none()
+        && sig.generics.where_clause.is_none()
+        && sig.asyncness.is_none()
+        && sig.unsafety.is_none()
+        && !mentions(sig.inputs.to_token_stream(), &impl_trait)
+        && !mentions(sig.output.to_token_stream(), &impl_trait)
+}
+
+// `tokens` with the lifetimes in `names` made `'static`
+fn static_lifetimes(
+    tokens: proc_macro2::TokenStream,
+    names: &[&syn::Ident],
+) -> proc_macro2::TokenStream {
+    let mut result = proc_macro2::TokenStream::new();
+    let mut lifetime = false;
+    for tree in tokens {
+        let tree = match tree {
+            proc_macro2::TokenTree::Group(group) => {
+                let mut replaced = proc_macro2::Group::new(
+                    group.delimiter(),
+                    static_lifetimes(group.stream(), names),
+                );
+                replaced.set_span(group.span());
+                replaced.into()
+            }
+            proc_macro2::TokenTree::Ident(ident) if lifetime && names.contains(&&ident) => {
+                proc_macro2::Ident::new("static", ident.span()).into()
+            }
+            tree => tree,
+        };
+        lifetime = matches!(&tree, proc_macro2::TokenTree::Punct(p) if p.as_char() == '\'');
+        result.extend(Some(tree));
+    }
+    result
+}
+
+// the part of `__call_dyn` calling one arity of an overload through its set, if the arguments
+// have its parameter types. the lifetimes of the overload become `'static`, the elided ones are
+// inferred as such.
+fn dyn_call(
+    sig: &syn::Signature,
+    input_types: &[syn::Type],
+    output: &proc_macro2::TokenStream,
+    returns: bool,
+) -> proc_macro2::TokenStream {
+    if !dyn_callable(sig) {
+        return quote!();
+    }
+    let lifetimes: Vec<_> = sig
+        .generics
+        .lifetimes()
+        .map(|l| &l.lifetime.ident)
+        .collect();
+    let types: Vec<_> = input_types
+        .iter()
+        .map(|ty| static_lifetimes(ty.to_token_stream(), &lifetimes))
+        .collect();
+    let count = types.len();
+    let arity = if count == 0 {
+        quote!(__overloadf_args.is_empty())
+    } else {
+        quote!(__overloadf_args.len() == #count)
+    };
+    let indices = 0..count;
+    let taken = quote!((#(::overloadf::__dyn_take::<#types>(__overloadf_args),)*));
+    let call = if returns {
+        let output = static_lifetimes(output.clone(), &lifetimes);
+        quote!(::overloadf::ReturnOverload::<(#(#types,)*), #output>::call_returning(self, #taken))
+    } else {
+        let call = dispatch_call(quote!(__overloadf_set), taken);
+        quote!({
+            let __overloadf_set = *self;
+            #call
+        })
+    };
+    quote!(
+        if #arity #(&& ::overloadf::__dyn_is::<#types>(&*__overloadf_args[#indices]))* {
+            return Some(::overloadf::__dyn_box(#call));
+        }
+    )
+}
+
+fn generic_args(generics: &syn::Generics) -> Vec<proc_macro2::TokenStream> {
+    generics
+        .params
+        .iter()
+        .map(|param| match param {
+            syn::GenericParam::Lifetime(l) => l.lifetime.to_token_stream(),
+            syn::GenericParam::Type(t) => t.ident.to_token_stream(),
+            syn::GenericParam::Const(c) => c.ident.to_token_stream(),
+        })
+        .collect()
+}
+
+// generics of the overloader impls of a trait method: those of the trait, the implementing type
+// bounded by the trait, and those of the method
+fn trait_method_generics(context: &TraitContext, method: &syn::Generics) -> syn::Generics {
+    let this = &context.this;
+    let path = &context.path;
+    merge_generics(Some(parse_quote!(#this: #path)), &context.generics, method)
+}
+
+// the generics of the trait or impl block (`outer`) together with those of a method (`inner`),
+// with `first` ahead of their type parameters. lifetimes go first.
+fn merge_generics(
+    first: Option<syn::GenericParam>,
+    outer: &syn::Generics,
+    inner: &syn::Generics,
+) -> syn::Generics {
+    let mut lifetimes = vec![];
+    let mut params: Vec<syn::GenericParam> = first.into_iter().collect();
+    for param in outer.params.iter().chain(&inner.params) {
+        let mut param = param.clone();
+        // defaults are not allowed on impls
+        match &mut param {
+            syn::GenericParam::Lifetime(_) => {
+                lifetimes.push(param);
+                continue;
+            }
+            syn::GenericParam::Type(t) => {
+                t.eq_token = None;
+                t.default = None;
+            }
+            syn::GenericParam::Const(c) => {
+                c.eq_token = None;
+                c.default = None;
+            }
+        }
+        params.push(param);
+    }
+    let predicates: Vec<_> = outer
+        .where_clause
+        .iter()
+        .chain(&inner.where_clause)
+        .flat_map(|where_clause| &where_clause.predicates)
+        .collect();
+    let mut generics: syn::Generics = parse_quote!(<#(#lifetimes,)* #(#params),*>);
+    if !predicates.is_empty() {
+        generics.where_clause = Some(parse_quote!(where #(#predicates),*));
+    }
+    generics
+}
+
+// the overload as a hidden method of the trait. with the `send` option an async overload returns
+// a future that is declared `Send`, which the impls have to live up to.
+fn trait_overload_method(
+    method: &syn::TraitItemFn,
+    index: usize,
+    options: &options::Options,
+) -> syn::TraitItemFn {
+    let mut method = method.clone();
+    method.sig.ident = hidden_method(&method.sig.ident, index);
+    strip_defaults(&mut method.sig);
+    method.attrs.push(parse_quote!(#[doc(hidden)]));
+    if method.sig.asyncness.is_some() {
+        // its future is only reached through the overloader, which states its bounds
+        method.attrs.push(parse_quote!(#[allow(async_fn_in_trait)]));
+    }
+    if method.sig.asyncness.is_some() && options.send {
+        method.sig.asyncness = None;
+        let output = match &method.sig.output {
+            syn::ReturnType::Default => quote!(()),
+            syn::ReturnType::Type(_, t) => quote!(#t),
+        };
+        method.sig.output = parse_quote!(-> impl core::future::Future<Output = #output> + Send);
+        method.default = method
+            .default
+            .map(|block| parse_quote!({ async move #block }));
+    }
+    method
+}
+
+// the Fn* impls of one overload of a trait method, generic over the implementing type, one for
+// each arity its default parameters allow. they call the hidden trait method the overload became,
+// so that default bodies stay in the trait, and evaluate omitted parameters themselves, so that
+// defaults declared in the trait hold for every implementor.
+fn trait_overload_impl(
+    context: &TraitContext,
+    method: &syn::TraitItemFn,
+    index: usize,
+    shared_type: &syn::Ident,
+    ext: Option<&MethodExt>,
+    options: &options::Options,
+) -> proc_macro2::TokenStream {
+    let sig = &method.sig;
+    let this = &context.this;
+    let qualified = &context.qualified;
+    let generics = trait_method_generics(context, &self_generics(&sig.generics, this, qualified));
+    let this_type: syn::Type = parse_quote!(#this);
+    let mut input_types = Vec::<syn::Type>::new();
+    // the parameters go by their names, so that defaults can refer to them
+    let mut ids = vec![];
+    let mut default_values = vec![];
+    for (i, input) in sig.inputs.iter().enumerate() {
+        match input {
+            syn::FnArg::Receiver(r) => {
+                ids.push(format_ident!("__self"));
+                input_types.push(receiver_param(r, &this_type).0);
+                default_values.push(None);
+            }
+            syn::FnArg::Typed(t) => {
+                ids.push(match &*t.pat {
+                    syn::Pat::Ident(p) if p.subpat.is_none() => p.ident.clone(),
+                    _ => format_ident!("__overloadf_param_{}", i),
+                });
+                input_types.push(context.replace_self(&t.ty));
+                default_values.push(default_value(&t.attrs).map(|assign| {
+                    let assign = self_tokens(assign.to_token_stream(), this, qualified);
+                    syn::parse2(rename_self(assign)).unwrap()
+                }));
+            }
+        }
+    }
+    let output = match &sig.output {
+        syn::ReturnType::Default => quote!(()),
+        syn::ReturnType::Type(_, t) => self_tokens(t.to_token_stream(), this, qualified),
+    };
+    let hidden = hidden_method(&sig.ident, index);
+    let call = quote!(#qualified::#hidden(#(#ids),*));
+    let call = if sig.unsafety.is_some() {
+        quote!(unsafe { #call })
+    } else {
+        call
+    };
+    let attrs: Vec<syn::Attribute> = vec![];
+    let constness: Option<Token![const]> = None;
+    let tp = &context.overloader_args;
+    let method = &sig.ident;
+    let pats = ids.iter().map(|id| parse_quote!(#id)).collect();
+    let mut results = vec![];
+    let variants = param_variants(sig, input_types, pats, default_values, None, false);
+    for input_iter::Variant {
+        inputs: input_types,
+        params,
+        bindings: defaults,
+        ..
+    } in variants
+    {
+        let mut generics = generics.clone();
+        let receiver_types = input_types.clone();
+        let input_types = quotation_expand!(input_types);
+        let mut opaque = None;
+        let (call, output) = if sig.asyncness.is_some() {
+            let send = if options.send {
+                quote!(+ Send)
+            } else {
+                quote!()
+            };
+            if options.unboxed && !backend::stable() {
+                let alias = future_alias(shared_type, None, &input_types);
+                let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+                let declaration = quote!(
+                    #[doc(hidden)]
+                    #[allow(non_camel_case_types, private_interfaces, private_bounds)]
+                    pub type #alias #impl_generics #where_clause =
+                        impl core::future::Future<Output = #output> #send;
+                );
+                let alias_output = quote!(#alias #ty_generics);
+                opaque = Some((alias, declaration));
+                (call.clone(), alias_output)
+            } else {
+                // a boxed future is `'static`, and so has to be everything it captures
+                let params: Vec<_> = generics.type_params().map(|t| t.ident.clone()).collect();
+                let where_clause = generics.make_where_clause();
+                for param in params {
+                    where_clause.predicates.push(parse_quote!(#param: 'static));
+                }
+                (
+                    quote!(Box::pin(#call)),
+                    quote!(core::pin::Pin<Box<dyn core::future::Future<Output = #output> #send>>),
+                )
+            }
+        } else {
+            (call.clone(), output.clone())
+        };
+        let params = quotation_expand!(params);
+        let block = quote!(
+            let #params = args;
+            #(#defaults)*
+            #call
+        );
+        let (impl_generics, _ty_generics, where_clause) = generics.split_for_impl();
+        let result = if sig.unsafety.is_some() {
+            unsafe_impl!(
+                impl_generics,
+                input_types,
+                shared_type,
+                where_clause,
+                output,
+                attrs,
+                block,
+                opaque,
+                tp
+            )
+        } else {
+            fn_impl!(
+                impl_generics,
+                input_types,
+                shared_type,
+                where_clause,
+                output,
+                attrs,
+                block,
+                opaque,
+                constness,
+                tp
+            )
+        };
+        let ext_impl = match ext {
+            Some(ext) if sig.unsafety.is_none() && has_receiver([sig]) => method_ext_impl(
+                ext,
+                method,
+                quote!(#qualified::#method),
+                &generics,
+                &receiver_types,
+                &output,
+                Some(&quote!(#shared_type<#tp>)),
+            ),
+            _ => quote!(),
+        };
+        results.push(quote!(
+            #result
+            #ext_impl
+        ));
+    }
+    quote!(#(#results)*)
+}
+
+// `Xdd! { impl .. }`: hands an impl of trait `Xdd` to `__overload_impl`, together with the
+// overloaded methods of the trait and the signatures of their overloads. named like the trait, so
+// that `#[overload] impl` finds it by the path of the trait, from wherever the trait is visible:
+// through re-exports, `use .. as` and from other crates. the macro of a public trait is a
+// `#[macro_export]` one, named after the place of the trait so that traits of the same name don't
+// clash at the crate root. it is re-exported by its name in the module (a path to the crate root
+// would be refused for a macro-expanded export), which works on both backends.
+fn trait_macro(
+    vis: &syn::Visibility,
+    ident: &syn::Ident,
+    meta: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let body = quote!(::overloadf::__overload_impl! { { #meta } $($item)* });
+    match vis {
+        syn::Visibility::Public(_) => {
+            use std::hash::{Hash, Hasher};
+            let site = proc_macro::Span::call_site();
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            (site.file(), site.line(), site.column()).hash(&mut hasher);
+            let hidden = format_ident!("__overloadf_trait_{}_{:x}", ident, hasher.finish());
+            quote!(
+                #[doc(hidden)]
+                #[macro_export]
+                macro_rules! #hidden {
+                    ($($item:tt)*) => {
+                        #body
+                    };
+                }
+                #[doc(hidden)]
+                pub use #hidden as #ident;
+            )
+        }
+        _ => {
+            let hidden = format_ident!("__overloadf_trait_{}", ident);
+            quote!(
+                #[doc(hidden)]
+                #[allow(unused_macros)]
+                macro_rules! #hidden {
+                    ($($item:tt)*) => {
+                        #body
+                    };
+                }
+                #[allow(unused_imports)]
+                #vis use #hidden as #ident;
+            )
+        }
+    }
+}
+
+// methods of the trait that share a name become overloads: hidden trait methods, named by their
+// position, and an associated const of the overloader type, whose Fn* impls are generated here
+// once for every implementing type. impls only have to rename their methods to match (see
+// `process_trait_impl`).
+fn process_trait(mut item: syn::ItemTrait, options: &options::Options) -> TokenStream {
+    let ident = item.ident.clone();
+    let context = TraitContext::new(&item);
+    let overloader = overloader_generics(&item.generics, Some(&context.this));
+    // the overloader as the trait sees it, with `Self` as the implementing type
+    let const_args = generic_args(&overloader).into_iter().map(|arg| {
+        if context.this == arg.to_string() {
+            quote!(Self)
+        } else {
+            arg
+        }
+    });
+    let const_args = quote!(<#(#const_args),*>);
+    let vis = item.vis.clone();
+    // ordered maps, so that the expansion doesn't depend on hashing
+    let mut map: BTreeMap<String, Vec<syn::TraitItemFn>> = BTreeMap::new();
+    let mut items = vec![];
+    for i in &item.items {
+        if let syn::TraitItem::Fn(item_method) = i {
+            map.entry(item_method.sig.ident.to_string())
+                .or_default()
+                .push(item_method.clone());
+        } else {
+            items.push(i.clone());
+        }
+    }
+    let mut meta = vec![];
+    let mut prepares = vec![];
+    for (s, i) in map.iter() {
+        // a method with default parameters is a set of its own
+        for method in i.iter().filter(|m| has_variadic_param(&m.sig)) {
+            diagnostic::error(
+                method.sig.span(),
+                "variadic parameters aren't supported in traits",
+            );
+        }
+        let overloaded = i.len() > 1 || has_default_param(&i[0].sig);
+        if overloaded {
+            let duplicates = check_overlaps(i.iter().map(|m| &m.sig), false);
+            let overloads: Vec<_> = i
+                .iter()
+                .zip(duplicates)
+                .filter(|(_, duplicate)| !duplicate)
+                .map(|(m, _)| m)
+                .collect();
+            let const_field = format_ident!("{}", s);
+            let shared_type = format_ident!("Overloader_{}_{}", ident, s);
+            let docs = set_docs(
+                "Overloaded method, with the overloads:",
+                overloads.iter().map(|m| (&m.sig, &m.attrs[..])),
+            );
+            let const_stream: TokenStream = quote!(
+                    #docs
+                    #[allow(non_upper_case_globals)]
+                    const #const_field: #shared_type #const_args = #shared_type(core::marker::PhantomData);
+                ).into();
+            let t = syn::parse_macro_input!(const_stream as syn::TraitItemConst);
+            items.push(syn::TraitItem::Const(t));
+            let dispatch = dispatch_declaration(&vis, &shared_type, Some(&overloader));
+            let unsafety = i[0].sig.unsafety.is_some();
+            for m in i.iter().filter(|m| m.sig.unsafety.is_some() != unsafety) {
+                mixed_safety(&m.sig);
+            }
+            let ext = if !unsafety && has_receiver(i.iter().map(|m| &m.sig)) {
+                Some(MethodExt::new(
+                    format_ident!("OverloaderExt_{}_{}", ident, s),
+                    overloads.iter().map(|m| &m.sig),
+                ))
+            } else {
+                None
+            };
+            let ext_declaration = match &ext {
+                Some(ext) => method_ext_declaration(&vis, ext, &const_field, true),
+                None => quote!(),
+            };
+            let declaration = overloader_declaration(&vis, &shared_type, &overloader);
+            let reflection = set_reflection(
+                &shared_type,
+                &overloader,
+                &format!("{}::{}", ident, s),
+                listed_overloads(overloads.iter().map(|m| &m.sig)),
+            );
+            prepares.push(quote!(
+                #declaration
+                #dispatch
+                #ext_declaration
+                #reflection
+            ));
+            let mut signatures = vec![];
+            for (index, method) in overloads.into_iter().enumerate() {
+                let signature = sig_normalize(&method.sig);
+                let defaulted = default_positions(&method.sig);
+                signatures.push(if method.default.is_some() {
+                    quote!(default #signature #(#defaulted)*)
+                } else {
+                    quote!(#signature #(#defaulted)*)
+                });
+                items.push(syn::TraitItem::Fn(trait_overload_method(
+                    method, index, options,
+                )));
+                prepares.push(trait_overload_impl(
+                    &context,
+                    method,
+                    index,
+                    &shared_type,
+                    ext.as_ref(),
+                    options,
+                ));
+            }
+            meta.push(quote!(#const_field [#(#signatures),*]));
+        } else {
+            items.push(syn::TraitItem::Fn(i[0].clone()));
+        }
+    }
+    item.items = items;
+    let generics = &item.generics;
+    let trait_macro = trait_macro(&vis, &ident, quote!(#generics #(#meta)*));
+    let result = quote!(
+        #(#prepares)*
+        #trait_macro
+        #item
+    );
+    result.into()
+}
+
+fn replace_self<F: ToTokens, T: ToTokens, O: syn::parse::Parse>(
+    input: F,
+    to: T,
+) -> syn::parse::Result<O> {
+    let input_str = input
+        .to_token_stream()
+        .to_string()
+        .replace("Self", &to.to_token_stream().to_string())
+        .replace("self", "__self");
+    syn::parse_str(&input_str)
+}
+
+// type and binding of the `self` receiver, which becomes the first element of the argument tuple
+fn receiver_param(r: &syn::Receiver, tp: &syn::Type) -> (syn::Type, syn::Pat) {
+    let ty: syn::Type = match (&r.reference, &r.mutability) {
+        (Some((_, lifetime)), Some(_)) => parse_quote!(&#lifetime mut #tp),
+        (Some((_, lifetime)), None) => parse_quote!(&#lifetime #tp),
+        // `self`, `mut self` and typed receivers such as `self: Box<Self>`
+        (None, _) => replace_self(&r.ty, tp).unwrap(),
+    };
+    let pid = syn::PatIdent {
+        attrs: vec![],
+        by_ref: None,
+        mutability: if r.reference.is_none() {
+            r.mutability
+        } else {
+            None
+        },
+        ident: format_ident!("__self"),
+        subpat: None,
+    };
+    (ty, pid.into())
+}
+
+// the call body of one overload together with its final output type. binds the argument
+// tuple, evaluates omitted default parameters and wraps the future of async overloads: boxed by
+// default, or named `future_alias` through `type_alias_impl_trait` with the `unboxed` option. the
+// last element is the alias and its declaration, when one was made.
+#[allow(clippy::type_complexity)]
+fn call_body(
+    sig: &syn::Signature,
+    param_assign: &proc_macro2::TokenStream,
+    defaults: &[proc_macro2::TokenStream],
+    body: &[syn::Stmt],
+    output: &proc_macro2::TokenStream,
+    future_alias: syn::Ident,
+    options: &options::Options,
+) -> (
+    proc_macro2::TokenStream,
+    proc_macro2::TokenStream,
+    Option<(syn::Ident, proc_macro2::TokenStream)>,
+) {
+    let span = sig.span();
+    let mut opaque = None;
+    let (body, output) = if sig.asyncness.is_some() {
+        // spanned at the signature, so that errors about the future point at the overload
+        let future = quote_spanned!(span=> async move { #(#body)* });
+        let send = if options.send {
+            quote!(+ Send)
+        } else {
+            quote!()
+        };
+        if options.unboxed && !backend::stable() {
+            let (impl_generics, ty_generics, where_clause) = sig.generics.split_for_impl();
+            let declaration = quote_spanned!(span=>
+                // public: it is the `Output` of the (public) Fn* impls of the set
+                #[doc(hidden)]
+                #[allow(non_camel_case_types, private_interfaces, private_bounds)]
+                pub type #future_alias #impl_generics #where_clause =
+                    impl core::future::Future<Output = #output> #send;
+            );
+            let alias_output = quote!(#future_alias #ty_generics);
+            opaque = Some((future_alias, declaration));
+            (future, alias_output)
+        } else if options.send {
+            (
+                quote_spanned!(span=>
+                    fn overload_future_must_be_send<F>(future: F) -> F
+                    where
+                        F: core::future::Future + Send,
+                    {
+                        future
+                    }
+                    Box::pin(overload_future_must_be_send(#future))
+                ),
+                quote!(
+                    core::pin::Pin<Box<dyn core::future::Future<Output = #output> + Send>>
+                ),
+            )
+        } else {
+            (
+                quote!(Box::pin(#future)),
+                quote!(core::pin::Pin<Box<dyn core::future::Future<Output = #output>>>),
+            )
+        }
+    } else {
+        (quote!(#(#body)*), output.clone())
+    };
+    // the body of an unsafe overload may use unsafe operations, like the body of an `unsafe fn`
+    let body = if sig.unsafety.is_some() {
+        quote!(unsafe { #body })
+    } else {
+        body
+    };
+    (
+        quote!(
+            let #param_assign = args;
+            #(#defaults)*
+            #body
+        ),
+        output,
+        opaque,
+    )
+}
+
+// name of the future of one overload, unique among all overloads of the set
+fn future_alias(
+    shared_type: &syn::Ident,
+    tp: Option<&syn::Type>,
+    input_types: &proc_macro2::TokenStream,
+) -> syn::Ident {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    quote!(#tp #input_types).to_string().hash(&mut hasher);
+    format_ident!("{}_Future_{:x}", shared_type, hasher.finish())
+}
+
+struct IdentGen(pub u32);
+impl IdentGen {
+    #[inline]
+    pub fn new() -> Self {
+        Self(0)
+    }
+    #[inline]
+    pub fn ident(&mut self) -> syn::Ident {
+        self.0 += 1;
+        syn::Ident::new(&format!("_{}", self.0), proc_macro2::Span::call_site())
+    }
+    #[inline]
+    pub fn id(&self, id: u32) -> syn::Ident {
+        syn::Ident::new(&format!("_{}", id), proc_macro2::Span::call_site())
+    }
+}
+
+fn generics_normalize(ast: &syn::Generics) -> syn::Generics {
+    let mut gen = ast.clone();
+    let mut matching_table = HashMap::new();
+    let mut idgen = IdentGen::new();
+    for gen_param in gen.params.iter_mut() {
+        match gen_param {
+            syn::GenericParam::Type(t) => {
+                matching_table
+                    .entry(t.ident.to_string())
+                    .and_modify(|e| {
+                        t.ident = idgen.id(*e);
+                    })
+                    .or_insert_with(|| {
+                        t.ident = idgen.ident();
+                        idgen.0
+                    });
+            }
+            syn::GenericParam::Lifetime(l) => {
+                matching_table
+                    .entry(l.lifetime.ident.to_string())
+                    .and_modify(|e| {
+                        l.lifetime.ident = idgen.id(*e);
+                    })
+                    .or_insert_with(|| {
+                        l.lifetime.ident = idgen.ident();
+                        idgen.0
+                    });
+                for ll in l.bounds.iter_mut() {
+                    matching_table
+                        .entry(ll.ident.to_string())
+                        .and_modify(|e| {
+                            ll.ident = idgen.id(*e);
+                        })
+                        .or_insert_with(|| {
+                            ll.ident = idgen.ident();
+                            idgen.0
+                        });
+                }
+            }
+            syn::GenericParam::Const(_) => {
+                // left untouched, should not have any renaming here
+            }
+        }
+    }
+    if let Some(mut wh) = gen.where_clause.clone() {
+        let mut p = syn::punctuated::Punctuated::<_, syn::Token![,]>::new();
+        for predicate in wh.predicates.iter_mut() {
+            let mut take = true;
+            match predicate {
+                syn::WherePredicate::Type(t) => {
+                    if let Some(l) = &mut t.lifetimes {
+                        for ll in l.lifetimes.iter_mut() {
+                            if let syn::GenericParam::Lifetime(lp) = ll {
+                                if let Some(v) = matching_table.get(&lp.lifetime.ident.to_string())
+                                {
+                                    lp.lifetime.ident = idgen.id(*v);
+                                    for lll in lp.bounds.iter_mut() {
+                                        if let Some(v) = matching_table.get(&lll.ident.to_string())
+                                        {
+                                            lll.ident = idgen.id(*v);
+                                        } else {
+                                            take = false;
                                         }
                                     }
                                 } else {
@@ -307,231 +1916,1694 @@ fn generics_normalize(ast: &syn::Generics) -> syn::Generics {
                 }
                 &mut _ => todo!(),
             }
-            if take {
-                p.push(predicate.clone());
+            if take {
+                p.push(predicate.clone());
+            }
+        }
+        wh.predicates = p;
+        gen.where_clause = Some(wh);
+    }
+    gen
+}
+
+fn sig_normalize(sig: &syn::Signature) -> String {
+    let mut sig = sig.clone();
+    let underscore_token: syn::token::Underscore = syn::parse_str("_").unwrap();
+    for i in sig.inputs.iter_mut() {
+        if let syn::FnArg::Typed(pt) = i {
+            pt.attrs.clear();
+            pt.pat = Box::new(syn::Pat::Wild(syn::PatWild {
+                attrs: vec![],
+                underscore_token,
+            }));
+        }
+    }
+    // not going to work for all cases, but should be enough
+    sig.generics = generics_normalize(&sig.generics);
+    // rust doesn't differentiate functions by their output
+    // neither could fn traits do (`returns` sets add it back, see `duplicate_key`)
+    sig.output = syn::ReturnType::Default;
+    sig.into_token_stream().to_string()
+}
+
+// the self type of an inherent impl as part of an identifier: `crate::m::Stack<T>` becomes
+// `crate_m_Stack_T`
+fn type_name(tp: &syn::Type) -> String {
+    let mut name = String::new();
+    for c in tp.to_token_stream().to_string().chars() {
+        if c.is_alphanumeric() || c == '_' {
+            name.push(c);
+        } else if !name.is_empty() && !name.ends_with('_') {
+            name.push('_');
+        }
+    }
+    name.trim_end_matches('_').to_string()
+}
+
+// the self type as it can be written in expressions, `Stack::<T>` for `Stack<T>`
+fn expr_type(tp: &syn::Type) -> syn::Type {
+    let mut tp = tp.clone();
+    if let syn::Type::Path(path) = &mut tp {
+        for segment in path.path.segments.iter_mut() {
+            if let syn::PathArguments::AngleBracketed(args) = &mut segment.arguments {
+                args.colon2_token = Some(Default::default());
+            }
+        }
+    }
+    tp
+}
+
+// the Fn* impls of one overload of an inherent method, one for each arity its default
+// parameters allow, and its part of `__call_dyn`. the overloader carries the parameters of the
+// impl, which its impls merge with those of the method.
+fn impl_method_to_non_trait(
+    tp: &syn::Type,
+    impl_generics: &syn::Generics,
+    ast: &syn::ImplItemFn,
+    ext: Option<&MethodExt>,
+    options: &options::Options,
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    let generics = merge_generics(None, impl_generics, &ast.sig.generics);
+    let attrs = &ast.attrs;
+    let ident = ast.sig.ident.clone().into_token_stream().to_string();
+    let shared_type = format_ident!("Overloader_{}_{}", type_name(tp), ident);
+    let qualified = quote!(<#tp>);
+    let inputs = &ast.sig.inputs;
+    let output = match &ast.sig.output {
+        syn::ReturnType::Default => quote!(()),
+        syn::ReturnType::Type(_, t) => self_tokens(t.to_token_stream(), tp, &qualified),
+    };
+    let mut input_types = Vec::<syn::Type>::new();
+    let mut param_assign = Vec::<syn::Pat>::new();
+    let mut default_values = vec![];
+    for itp in inputs.iter() {
+        match itp {
+            syn::FnArg::Typed(itp) => {
+                let ty = self_tokens(itp.ty.to_token_stream(), tp, &qualified);
+                input_types.push(syn::parse2(ty).unwrap());
+                param_assign.push((*itp.pat).clone());
+                // evaluated in the body, where `self` is bound as `__self`
+                default_values.push(
+                    default_value(&itp.attrs)
+                        .map(|assign| replace_self(assign, expr_type(tp)).unwrap()),
+                );
+            }
+            syn::FnArg::Receiver(r) => {
+                let (ty, pat) = receiver_param(r, tp);
+                input_types.push(ty);
+                param_assign.push(pat);
+                default_values.push(None);
+            }
+        }
+    }
+    let new_block: syn::Block = replace_self(&ast.block, expr_type(tp)).unwrap();
+    let body = &new_block.stmts;
+    // the future alias of an unboxed async overload takes the merged generics
+    let mut sig = ast.sig.clone();
+    sig.generics = generics.clone();
+    let constness = const_impl(&ast.sig);
+    let tp_args = generic_args(&overloader_generics(impl_generics, None));
+    let tp_args = quote!(#(#tp_args),*);
+    let (impl_generics, _ty_generics, where_clause) = generics.split_for_impl();
+    let method = &ast.sig.ident;
+    let mut results = vec![];
+    let mut dyn_calls = vec![];
+    let variadic = variadic_param(&ast.sig);
+    let param_iter = param_variants(
+        &ast.sig,
+        input_types,
+        param_assign,
+        default_values,
+        variadic,
+        false,
+    );
+    for input_iter::Variant {
+        inputs: input_types,
+        params: param_assign,
+        bindings: defaults,
+        ..
+    } in param_iter
+    {
+        dyn_calls.push(dyn_call(&ast.sig, &input_types, &output, false));
+        let receiver_types = input_types.clone();
+        let param_assign = quotation_expand!(param_assign);
+        let input_types = quotation_expand!(input_types);
+        let alias = future_alias(&shared_type, None, &input_types);
+        let (block, output, opaque) = call_body(
+            &sig,
+            &param_assign,
+            &defaults,
+            body,
+            &output,
+            alias,
+            options,
+        );
+        let result = if ast.sig.unsafety.is_some() {
+            unsafe_impl!(
+                impl_generics,
+                input_types,
+                shared_type,
+                where_clause,
+                output,
+                attrs,
+                block,
+                opaque,
+                tp_args
+            )
+        } else {
+            fn_impl!(
+                impl_generics,
+                input_types,
+                shared_type,
+                where_clause,
+                output,
+                attrs,
+                block,
+                opaque,
+                constness,
+                tp_args
+            )
+        };
+        let ext_impl = match ext {
+            Some(ext) if ast.sig.unsafety.is_none() && has_receiver([&ast.sig]) => method_ext_impl(
+                ext,
+                method,
+                quote!(<#tp>::#method),
+                &generics,
+                &receiver_types,
+                &output,
+                None,
+            ),
+            _ => quote!(),
+        };
+        results.push(quote!(
+            #result
+            #ext_impl
+        ));
+    }
+    (quote!(#(#results)*), quote!(#(#dyn_calls)*))
+}
+
+fn process_impl(mut item: syn::ItemImpl, options: &options::Options) -> TokenStream {
+    let self_ty = item.self_ty.clone();
+    let self_type = &*self_ty;
+    let mut generated = vec![];
+    let mut items = vec![];
+    if let Some((_, path, _)) = &item.trait_ {
+        // impl Trait for Struct {}: the macro of the trait (see `trait_macro`) is found by the
+        // path of the trait, and passes the impl on to `process_trait_impl`
+        let mut path = path.clone();
+        for segment in path.segments.iter_mut() {
+            segment.arguments = syn::PathArguments::None;
+        }
+        return quote!(#path! { #item }).into();
+    } else {
+        // normal impl Struct {}, or impl<T: Bound> Struct<T> {}: the overloaders take the
+        // parameters of the impl
+        let overloader = overloader_generics(&item.generics, None);
+        let mut fn_names = HashSet::new();
+        let mut dup = HashSet::new();
+        let mut undefined = HashSet::new();
+        let mut with_receiver = HashSet::new();
+        let mut unsafety = HashMap::new();
+        // the set takes the visibility of its first overload
+        let mut visibility: HashMap<String, &syn::Visibility> = HashMap::new();
+        for i in &item.items {
+            if let syn::ImplItem::Fn(item_method) = i {
+                let method_id = item_method.sig.ident.to_string();
+                visibility
+                    .entry(method_id.clone())
+                    .or_insert(&item_method.vis);
+                let is_unsafe = item_method.sig.unsafety.is_some();
+                if *unsafety.entry(method_id.clone()).or_insert(is_unsafe) != is_unsafe {
+                    mixed_safety(&item_method.sig);
+                }
+                if !is_unsafe && has_receiver([&item_method.sig]) {
+                    with_receiver.insert(method_id.clone());
+                }
+                // a method with default or variadic parameters is a set of its own
+                if !fn_names.insert(method_id.clone())
+                    || has_default_param(&item_method.sig)
+                    || has_variadic_param(&item_method.sig)
+                {
+                    dup.insert(method_id);
+                }
+            }
+        }
+        // overloads repeating an earlier one, by position in the impl
+        let mut duplicates = HashSet::new();
+        for method_id in &dup {
+            let overloads: Vec<_> = item
+                .items
+                .iter()
+                .enumerate()
+                .filter_map(|(index, i)| match i {
+                    syn::ImplItem::Fn(m) if &m.sig.ident.to_string() == method_id => {
+                        Some((index, &m.sig))
+                    }
+                    _ => None,
+                })
+                .collect();
+            let repeated = check_overlaps(overloads.iter().map(|(_, sig)| *sig), false);
+            for ((index, _), repeated) in overloads.iter().zip(repeated) {
+                if repeated {
+                    duplicates.insert(*index);
+                }
+            }
+        }
+        // the parts of `__call_dyn` of each set
+        let mut dyn_calls: BTreeMap<String, Vec<proc_macro2::TokenStream>> = BTreeMap::new();
+        for (index, i) in item.items.iter().enumerate() {
+            if let syn::ImplItem::Fn(item_method) = i {
+                let method_id = item_method.sig.ident.to_string();
+                if dup.get(&method_id).is_some() {
+                    if duplicates.contains(&index) {
+                        continue;
+                    }
+                    let vis = visibility[&method_id];
+                    let tp_str = type_name(self_type);
+                    let ext = if with_receiver.contains(&method_id) {
+                        let sigs = item
+                            .items
+                            .iter()
+                            .enumerate()
+                            .filter_map(|(index, i)| match i {
+                                syn::ImplItem::Fn(m)
+                                    if m.sig.ident == method_id && !duplicates.contains(&index) =>
+                                {
+                                    Some(&m.sig)
+                                }
+                                _ => None,
+                            });
+                        Some(MethodExt::new(
+                            format_ident!("OverloaderExt_{}_{}", tp_str, method_id),
+                            sigs,
+                        ))
+                    } else {
+                        None
+                    };
+                    if undefined.insert(method_id.clone()) {
+                        let const_field = &item_method.sig.ident;
+                        let shared_type = format_ident!("Overloader_{}_{}", tp_str, method_id);
+                        let (_, ty_generics, _) = overloader.split_for_impl();
+                        let listed: Vec<_> = item
+                            .items
+                            .iter()
+                            .enumerate()
+                            .filter_map(|(index, i)| match i {
+                                syn::ImplItem::Fn(m)
+                                    if m.sig.ident == method_id && !duplicates.contains(&index) =>
+                                {
+                                    Some(m)
+                                }
+                                _ => None,
+                            })
+                            .collect();
+                        let docs = set_docs(
+                            "Overloaded method, with the overloads:",
+                            listed.iter().map(|m| (&m.sig, &m.attrs[..])),
+                        );
+                        let const_stream: TokenStream = quote!(
+                            #docs
+                            #[allow(non_upper_case_globals)]
+                            #vis const #const_field: #shared_type #ty_generics = #shared_type(core::marker::PhantomData);
+                        )
+                        .into();
+                        let t = syn::parse_macro_input!(const_stream as syn::ImplItemConst);
+                        items.push(syn::ImplItem::Const(t));
+                        let declaration = overloader_declaration(vis, &shared_type, &overloader);
+                        let dispatch = dispatch_declaration(vis, &shared_type, Some(&overloader));
+                        let ext_declaration = match &ext {
+                            Some(ext) => method_ext_declaration(vis, ext, const_field, false),
+                            None => quote!(),
+                        };
+                        let reflection = set_reflection(
+                            &shared_type,
+                            &overloader,
+                            &format!("{}::{}", token_string(self_type), method_id),
+                            listed_overloads(listed.iter().map(|m| &m.sig)),
+                        );
+                        generated.push(quote!(
+                            #declaration
+                            #dispatch
+                            #ext_declaration
+                            #reflection
+                        ));
+                    }
+                    let (overload, dyn_call) = impl_method_to_non_trait(
+                        self_type,
+                        &item.generics,
+                        item_method,
+                        ext.as_ref(),
+                        options,
+                    );
+                    generated.push(overload);
+                    dyn_calls.entry(method_id).or_default().push(dyn_call);
+                } else {
+                    items.push(syn::ImplItem::Fn(item_method.clone()));
+                }
+            } else {
+                items.push(i.clone());
+            }
+        }
+        let tp_str = type_name(self_type);
+        for (method_id, calls) in dyn_calls {
+            let shared_type = format_ident!("Overloader_{}_{}", tp_str, method_id);
+            generated.push(set_dynamic(
+                &shared_type,
+                &item.generics,
+                &overloader,
+                quote!(#(#calls)* None),
+            ));
+        }
+    }
+    item.items = items;
+    let result = quote!(
+        #(#generated)*
+        #item
+    );
+    result.into()
+}
+
+// what `__overload_impl` gets from the macro of a trait: the generics of the trait, its
+// overloaded methods with their overloads, and the impl
+struct TraitImpl {
+    generics: syn::Generics,
+    sets: Vec<(syn::Ident, Vec<TraitOverload>)>,
+    item: syn::ItemImpl,
+}
+
+// `default "sig" 1 "= 5"`: an overload with a default body, its signature and its default
+// parameters (see `default_positions`)
+struct TraitOverload {
+    default: bool,
+    signature: syn::LitStr,
+    defaulted: Vec<(usize, String)>,
+}
+
+impl syn::parse::Parse for TraitOverload {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let default = input.parse::<Option<Token![default]>>()?.is_some();
+        let signature = input.parse()?;
+        let mut defaulted = vec![];
+        while input.peek(syn::LitInt) {
+            let index = input.parse::<syn::LitInt>()?.base10_parse()?;
+            defaulted.push((index, input.parse::<syn::LitStr>()?.value()));
+        }
+        Ok(Self {
+            default,
+            signature,
+            defaulted,
+        })
+    }
+}
+
+impl syn::parse::Parse for TraitImpl {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let content;
+        braced!(content in input);
+        let generics = content.parse()?;
+        let mut sets = vec![];
+        while !content.is_empty() {
+            let ident = content.parse()?;
+            let signatures;
+            bracketed!(signatures in content);
+            let mut overloads = vec![];
+            while !signatures.is_empty() {
+                overloads.push(signatures.parse()?);
+                if !signatures.is_empty() {
+                    signatures.parse::<Token![,]>()?;
+                }
+            }
+            sets.push((ident, overloads));
+        }
+        Ok(Self {
+            generics,
+            sets,
+            item: input.parse()?,
+        })
+    }
+}
+
+// the parameters of a trait, mapped to the arguments an impl gives them (or their defaults)
+fn trait_arguments(
+    generics: &syn::Generics,
+    path: &syn::Path,
+) -> HashMap<String, proc_macro2::TokenStream> {
+    let mut lifetimes = vec![];
+    let mut types = vec![];
+    if let Some(syn::PathArguments::AngleBracketed(arguments)) =
+        path.segments.last().map(|segment| &segment.arguments)
+    {
+        for argument in &arguments.args {
+            match argument {
+                syn::GenericArgument::Lifetime(l) => lifetimes.push(l.to_token_stream()),
+                syn::GenericArgument::Type(_) | syn::GenericArgument::Const(_) => {
+                    types.push(argument.to_token_stream())
+                }
+                _ => {}
+            }
+        }
+    }
+    let mut lifetimes = lifetimes.into_iter();
+    let mut types = types.into_iter();
+    let mut map = HashMap::new();
+    for param in &generics.params {
+        let (name, argument) = match param {
+            syn::GenericParam::Lifetime(l) => (l.lifetime.to_string(), lifetimes.next()),
+            syn::GenericParam::Type(t) => (
+                t.ident.to_string(),
+                types
+                    .next()
+                    .or_else(|| t.default.as_ref().map(ToTokens::to_token_stream)),
+            ),
+            syn::GenericParam::Const(c) => (
+                c.ident.to_string(),
+                types
+                    .next()
+                    .or_else(|| c.default.as_ref().map(ToTokens::to_token_stream)),
+            ),
+        };
+        if let Some(argument) = argument {
+            map.insert(name, argument);
+        }
+    }
+    map
+}
+
+// replaces the idents and lifetimes found in `arguments`, except those that follow a path
+// separator (associated types)
+fn substitute(
+    input: proc_macro2::TokenStream,
+    arguments: &HashMap<String, proc_macro2::TokenStream>,
+) -> proc_macro2::TokenStream {
+    use proc_macro2::TokenTree;
+    let mut output = proc_macro2::TokenStream::new();
+    let mut trees = input.into_iter().peekable();
+    let (mut colon, mut after_path) = (false, false);
+    while let Some(tree) = trees.next() {
+        let is_colon = matches!(&tree, TokenTree::Punct(p) if p.as_char() == ':');
+        match tree {
+            TokenTree::Punct(p) if p.as_char() == '\'' => {
+                let lifetime = match trees.peek() {
+                    Some(TokenTree::Ident(ident)) => arguments.get(&format!("'{}", ident)),
+                    _ => None,
+                };
+                match lifetime {
+                    Some(lifetime) => {
+                        trees.next();
+                        output.extend(lifetime.clone());
+                    }
+                    None => output.extend(Some(TokenTree::Punct(p))),
+                }
+            }
+            TokenTree::Ident(ident)
+                if !after_path && arguments.contains_key(&ident.to_string()) =>
+            {
+                output.extend(arguments[&ident.to_string()].clone())
+            }
+            TokenTree::Group(group) => {
+                let mut replaced = proc_macro2::Group::new(
+                    group.delimiter(),
+                    substitute(group.stream(), arguments),
+                );
+                replaced.set_span(group.span());
+                output.extend(Some(TokenTree::Group(replaced)));
+            }
+            tree => output.extend(Some(tree)),
+        }
+        after_path = colon && is_colon;
+        colon = is_colon;
+    }
+    output
+}
+
+// `#[overload] impl Trait for T`, with the overloads of the trait at hand: every overload is
+// renamed to the hidden trait method it implements, found by its signature once the parameters
+// of the trait are replaced by the arguments of the impl
+fn process_trait_impl(input: TraitImpl) -> TokenStream {
+    let TraitImpl {
+        generics,
+        sets,
+        mut item,
+    } = input;
+    let arguments = match &item.trait_ {
+        Some((_, path, _)) => trait_arguments(&generics, path),
+        None => HashMap::new(),
+    };
+    // the overloads with their signatures, as the impl writes them
+    let sets: Vec<(syn::Ident, Vec<(TraitOverload, String)>)> = sets
+        .into_iter()
+        .map(|(ident, overloads)| {
+            let overloads = overloads
+                .into_iter()
+                .map(|overload| {
+                    let signature = overload.signature.value();
+                    let tokens = substitute(signature.parse().unwrap(), &arguments);
+                    let signature = match syn::parse2(tokens) {
+                        Ok(sig) => sig_normalize(&sig),
+                        Err(_) => signature,
+                    };
+                    (overload, signature)
+                })
+                .collect();
+            (ident, overloads)
+        })
+        .collect();
+    let mut implemented = HashSet::new();
+    for i in item.items.iter_mut() {
+        let syn::ImplItem::Fn(item_method) = i else {
+            continue;
+        };
+        let ident = item_method.sig.ident.clone();
+        let overloads = sets
+            .iter()
+            .find(|(set, _)| *set == ident)
+            .map(|(_, overloads)| overloads);
+        let signature = sig_normalize(&item_method.sig);
+        let position = overloads.and_then(|overloads| {
+            overloads
+                .iter()
+                .position(|(_, s)| *s == signature)
+                .map(|index| (index, &overloads[index].0))
+        });
+        // an impl may repeat the defaults of the trait, but not add its own: calls go through
+        // the overloader of the trait, which knows only those
+        for (index, input) in item_method.sig.inputs.iter().enumerate() {
+            let syn::FnArg::Typed(tp) = input else {
+                continue;
+            };
+            let Some(assign) = default_value(&tp.attrs) else {
+                continue;
+            };
+            let declared = position.and_then(|(_, overload)| {
+                overload
+                    .defaulted
+                    .iter()
+                    .find(|(position, _)| *position == index)
+            });
+            match declared {
+                None => diagnostic::error(
+                    tp.span(),
+                    format!(
+                        "the trait declares no default for this parameter of `{}`. default \
+                         parameters of trait methods are declared in the trait",
+                        ident
+                    ),
+                ),
+                Some((_, value)) if *value != assign.to_token_stream().to_string() => {
+                    diagnostic::error(
+                        tp.span(),
+                        format!(
+                            "this default differs from the one the trait declares: `{}`",
+                            value
+                        ),
+                    )
+                }
+                Some(_) => {}
+            }
+        }
+        strip_defaults(&mut item_method.sig);
+        match (overloads, position) {
+            (_, Some((index, _))) => {
+                implemented.insert((ident.to_string(), index));
+                item_method.sig.ident = hidden_method(&ident, index);
+            }
+            (Some(_), None) => diagnostic::error(
+                item_method.sig.span(),
+                format!(
+                    "`{}` matches no overload of the trait. parameter types have to be written \
+                     the way the trait declares them",
+                    ident
+                ),
+            ),
+            (None, None) => {}
+        }
+    }
+    for (set, overloads) in &sets {
+        for (index, (overload, signature)) in overloads.iter().enumerate() {
+            if !overload.default && !implemented.contains(&(set.to_string(), index)) {
+                diagnostic::error(
+                    item.self_ty.span(),
+                    format!("missing overload of `{}`: `{}`", set, signature),
+                );
+            }
+        }
+    }
+    quote!(#item).into()
+}
+
+// the `#[default]` parameters as the macro of a trait passes them on to impls: their positions
+// followed by their values, `1 "= 5"`
+fn default_positions(sig: &syn::Signature) -> Vec<proc_macro2::TokenStream> {
+    sig.inputs
+        .iter()
+        .enumerate()
+        .filter_map(|(index, input)| match input {
+            syn::FnArg::Typed(tp) => default_value(&tp.attrs).map(|assign| {
+                let value = assign.to_token_stream().to_string();
+                quote!(#index #value)
+            }),
+            syn::FnArg::Receiver(_) => None,
+        })
+        .collect()
+}
+
+fn has_default_param(sig: &syn::Signature) -> bool {
+    sig.inputs.iter().any(|input| match input {
+        syn::FnArg::Typed(tp) => tp.attrs.iter().any(|attr| attr.path().is_ident("default")),
+        syn::FnArg::Receiver(_) => false,
+    })
+}
+
+fn has_variadic_param(sig: &syn::Signature) -> bool {
+    sig.inputs.iter().any(|input| match input {
+        syn::FnArg::Typed(tp) => tp.attrs.iter().any(|attr| attr.path().is_ident("variadic")),
+        syn::FnArg::Receiver(_) => false,
+    })
+}
+
+// the arities of the `#[variadic(max = 12)]` parameter of `sig`, which has to be the last one and
+// can't have a default
+fn variadic_param(sig: &syn::Signature) -> Option<fn_struct::Variadic> {
+    let last = sig.inputs.len().checked_sub(1)?;
+    let mut variadic = None;
+    for (i, input) in sig.inputs.iter().enumerate() {
+        let syn::FnArg::Typed(tp) = input else {
+            continue;
+        };
+        let arity = match fn_struct::variadic_arity(&tp.attrs) {
+            Some(Ok(arity)) => arity,
+            Some(Err(err)) => {
+                diagnostic::error(err.span(), err.to_string());
+                continue;
             }
+            None => continue,
+        };
+        if i != last {
+            diagnostic::error(tp.span(), "a variadic parameter has to be the last one");
+        } else if default_value(&tp.attrs).is_some() {
+            diagnostic::error(tp.span(), "a variadic parameter can't have a default");
+        } else if arity.min > arity.max {
+            diagnostic::error(
+                tp.span(),
+                format!(
+                    "a variadic parameter can't take at least {} and at most {} arguments",
+                    arity.min, arity.max
+                ),
+            );
+        } else {
+            variadic = Some(arity);
         }
-        wh.predicates = p;
-        gen.where_clause = Some(wh);
     }
-    gen
+    variadic
 }
 
-fn sig_normalize(sig: &syn::Signature) -> String {
-    let mut sig = sig.clone();
-    let underscore_token: syn::token::Underscore = syn::parse_str("_").unwrap();
-    for i in sig.inputs.iter_mut() {
-        if let syn::FnArg::Typed(pt) = i {
-            pt.pat = Box::new(syn::Pat::Wild(syn::PatWild {
-                attrs: vec![],
-                underscore_token,
-            }));
+// method declarations can't keep the `#[default]` attributes of their parameters
+fn strip_defaults(sig: &mut syn::Signature) {
+    for input in sig.inputs.iter_mut() {
+        if let syn::FnArg::Typed(tp) = input {
+            tp.attrs.retain(|attr| !attr.path().is_ident("default"));
         }
     }
-    // not going to work for all cases, but should be enough
-    sig.generics = generics_normalize(&sig.generics);
-    // rust doesn't differentiate functions by their output
-    // neither could fn traits do
-    sig.output = syn::ReturnType::Default;
-    sig.into_token_stream().to_string()
 }
 
-fn impl_method_to_non_trait(tp: &syn::Type, ast: &syn::ImplItemFn) -> proc_macro2::TokenStream {
-    let span = ast.span().unstable();
-    let (impl_generics, _ty_generics, where_clause) = &ast.sig.generics.split_for_impl();
-    let attrs = &ast.attrs;
-    let unsafety = &ast.sig.unsafety;
-    let asyncness = &ast.sig.asyncness;
-    let ident = ast.sig.ident.clone().into_token_stream().to_string();
-    let tp_str = tp.into_token_stream().to_string().replace(' ', "_");
-    let shared_type = format_ident!("Overloader_{}_{}", tp_str, ident);
-    let inputs = &ast.sig.inputs;
-    let mut output = get_output(&ast.sig.output, tp);
-    let mut input_types = Vec::<syn::Type>::new();
-    let mut input_params = vec![];
-    let mut param_assign = Vec::<syn::Pat>::new();
-    for (i, itp) in inputs.iter().enumerate() {
-        input_params.push(format_ident!("_{}", i));
-        match itp {
-            syn::FnArg::Typed(itp) => {
-                input_types.push(Box::leak(itp.ty.clone()).clone());
-                param_assign.push(Box::leak(itp.pat.clone()).clone());
-            }
-            syn::FnArg::Receiver(r) => {
-                let ty = match (r.reference.as_ref(), r.mutability.as_ref()) {
-                    (Some(_), Some(_)) => quote!(&mut #tp),
-                    (Some(_), None) => quote!(&#tp),
-                    (None, Some(_)) => quote!(mut #tp),
-                    (None, None) => quote!(#tp),
-                };
-                let id: syn::Ident = syn::parse_str("__self").unwrap();
-                let pid = syn::PatIdent {
-                    attrs: vec![],
-                    by_ref: None,
-                    mutability: None,
-                    ident: id,
-                    subpat: None,
+// the arities of an overload (see `InputIter`). reported instead are those that leave out every
+// parameter a type parameter of `sig` appears in, as nothing would determine it (unless the
+// return type does, with `returns`), and those that take the same types as an earlier one, which
+// calls couldn't tell apart.
+fn param_variants(
+    sig: &syn::Signature,
+    input_types: Vec<syn::Type>,
+    param_assign: Vec<syn::Pat>,
+    default_values: Vec<Option<fn_struct::Assign>>,
+    variadic: Option<fn_struct::Variadic>,
+    returns: bool,
+) -> Vec<input_iter::Variant> {
+    let params: Vec<_> = sig.inputs.iter().collect();
+    let describe = |variant: &input_iter::Variant| {
+        let mut parts = vec![];
+        if !variant.omitted.is_empty() {
+            let names: Vec<_> = variant
+                .omitted
+                .iter()
+                .map(|i| match params[*i] {
+                    syn::FnArg::Typed(tp) => tp.pat.to_token_stream().to_string(),
+                    syn::FnArg::Receiver(_) => "self".to_string(),
+                })
+                .collect();
+            parts.push(format!("leaving out `{}`", names.join("`, `")));
+        }
+        if variadic.is_some() {
+            parts.push(match variant.arity {
+                1 => "passing 1 variadic argument".to_string(),
+                arity => format!("passing {} variadic arguments", arity),
+            });
+        }
+        parts.join(" and ")
+    };
+    // the variadic parameter, where two arities differ in it only
+    let last_span = params.last().map_or(sig.span(), |param| param.span());
+    let mut variants: Vec<input_iter::Variant> = vec![];
+    let mut undetermined_reported = false;
+    // every arity is generated, so their number is capped (see `InputIter`)
+    let defaulted: Vec<_> = (0..default_values.len())
+        .filter(|i| default_values[*i].is_some())
+        .collect();
+    if let Some(i) = defaulted.get(input_iter::MAX_DEFAULTS) {
+        diagnostic::error(
+            params[*i].span(),
+            format!(
+                "`{}` has {} default parameters, but at most {} are supported: every subset of \
+                 them left out is an arity of its own",
+                sig.ident,
+                defaulted.len(),
+                input_iter::MAX_DEFAULTS
+            ),
+        );
+        return variants;
+    }
+    let count = variadic.map_or(1, |variadic| variadic.max - variadic.min + 1) << defaulted.len();
+    if count > input_iter::MAX_ARITIES {
+        diagnostic::error(
+            last_span,
+            format!(
+                "`{}` takes {} arities with its default and variadic parameters, but at most {} \
+                 are supported",
+                sig.ident,
+                count,
+                input_iter::MAX_ARITIES
+            ),
+        );
+        return variants;
+    }
+    let arities = input_iter::InputIter::new(input_types, param_assign, default_values, variadic);
+    for variant in arities {
+        let types = &variant.inputs;
+        let output = match &sig.output {
+            syn::ReturnType::Type(_, ty) if returns => quote!(#ty),
+            _ => quote!(),
+        };
+        let inputs = quote!(#(#types)* #output);
+        let undetermined: Vec<_> = sig
+            .generics
+            .type_params()
+            .filter(|param| !mentions(inputs.clone(), &param.ident))
+            .map(|param| param.ident.to_string())
+            .collect();
+        if !undetermined.is_empty() {
+            if !undetermined_reported {
+                let undetermined = undetermined.join("`, `");
+                let cause = if !variant.omitted.is_empty() {
+                    format!(
+                        "leaving out the default parameters of `{}` leaves `{}` undetermined",
+                        sig.ident, undetermined
+                    )
+                } else if variadic.is_some() && variant.arity == 0 {
+                    format!(
+                        "calling `{}` without variadic arguments leaves `{}` undetermined",
+                        sig.ident, undetermined
+                    )
+                } else {
+                    format!(
+                        "the parameters of `{}` leave `{}` undetermined",
+                        sig.ident, undetermined
+                    )
                 };
-                input_types.push(syn::parse_str(&ty.to_string()).unwrap());
-                param_assign.push(pid.into());
+                diagnostic::error(
+                    sig.span(),
+                    format!(
+                        "{}. a type parameter has to appear in a parameter without default, a \
+                         variadic one taking at least one argument, or the return type of a \
+                         function with `#[overload(returns)]`",
+                        cause
+                    ),
+                );
+                undetermined_reported = true;
             }
+            continue;
+        }
+        let arity = overlap::Overload::arity(sig, types);
+        let overlapping = variants.iter().find(|first| {
+            overlap::Overload::arity(sig, &first.inputs)
+                .overlap(&arity)
+                .is_some()
+        });
+        if let Some(first) = overlapping {
+            // pointed at the first parameter in which the two differ
+            let differs = |a: &[usize], b: &[usize]| a.iter().find(|i| !b.contains(i)).copied();
+            let span =
+                differs(&variant.omitted, &first.omitted).map_or(last_span, |i| params[i].span());
+            let note_span =
+                differs(&first.omitted, &variant.omitted).map_or(last_span, |i| params[i].span());
+            diagnostic::error_with_note(
+                span,
+                format!(
+                    "ambiguous default parameters of `{}`: {} takes `{}`, and {} takes `{}`, \
+                     calls of which can't be told apart",
+                    sig.ident,
+                    describe(&variant),
+                    tuple_string(types),
+                    describe(first),
+                    tuple_string(&first.inputs)
+                ),
+                note_span,
+                "colliding default parameter",
+            );
+            continue;
         }
+        variants.push(variant);
     }
-    let new_block: syn::Block = replace_self(&ast.block, tp).unwrap();
-    let body = &new_block.stmts;
-    let param_assign = quotation_expand!(param_assign);
-    let input_types = quotation_expand!(input_types);
-    let block;
-    if unsafety.is_some() {
-        span.warning(
-            "unsafe fn is not supported. ".to_owned()
-                + "Will wrap in a unsafe block to make function safe.",
-        )
-        .emit();
-        if asyncness.is_some() {
-            output = quote!(core::pin::Pin<Box<dyn core::future::Future<Output = #output>>>);
-            block = quote!(
-                let #param_assign = args;
-                unsafe {
-                    Box::pin(async move {
-                        #(#body)*
+    variants
+}
+
+fn tuple_string(types: &[syn::Type]) -> String {
+    quote!((#(#types),*)).to_string()
+}
+
+fn mentions(input: proc_macro2::TokenStream, ident: &syn::Ident) -> bool {
+    input.into_iter().any(|tree| match tree {
+        proc_macro2::TokenTree::Ident(i) => i == *ident,
+        proc_macro2::TokenTree::Group(group) => mentions(group.stream(), ident),
+        _ => false,
+    })
+}
+
+// the overloader struct and value of a free function set. `docs` documents the value.
+fn set_declaration(
+    vis: &syn::Visibility,
+    ident: &syn::Ident,
+    shared_type: &syn::Ident,
+    docs: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let dispatch = dispatch_declaration(vis, shared_type, None);
+    let struct_doc = format!(
+        "The overloads of [`{0}`](const@{0}), each documented on one of the empty `impl` blocks.",
+        ident
+    );
+    quote!(
+        #dispatch
+        #[doc = #struct_doc]
+        #[allow(non_camel_case_types)]
+        #[allow(dead_code)]
+        #[derive(Copy, Clone)]
+        #vis struct #shared_type;
+        unsafe impl Send for #shared_type {}
+        unsafe impl Sync for #shared_type {}
+        // a const rather than a static, so that const overloads work in const contexts
+        #docs
+        #[allow(non_upper_case_globals)]
+        #vis const #ident: #shared_type = #shared_type;
+    )
+}
+
+// the trait object a parameter of a `dynamic` overload dispatches on: `dyn Shape + 'static` for
+// `&dyn Shape`, or `None` for a parameter of a concrete type
+fn dispatched_object(ty: &syn::Type) -> Option<proc_macro2::TokenStream> {
+    fn unparen(ty: &syn::Type) -> &syn::Type {
+        match ty {
+            syn::Type::Paren(paren) => unparen(&paren.elem),
+            syn::Type::Group(group) => unparen(&group.elem),
+            ty => ty,
+        }
+    }
+    match unparen(ty) {
+        syn::Type::Reference(reference) => match unparen(&reference.elem) {
+            syn::Type::TraitObject(object) => {
+                let traits = object
+                    .bounds
+                    .iter()
+                    .filter(|bound| matches!(bound, syn::TypeParamBound::Trait(_)));
+                Some(quote!(dyn #(#traits)+* + 'static))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// the trait objects the parameters of a `dynamic` overload dispatch on (see
+// `dispatched_object`). reports what can't be dispatched on: the arguments are compared by
+// reference, as `&dyn Any`, and all dispatched ones are the same trait object.
+fn dynamic_params(sig: &syn::Signature) -> Vec<Option<proc_macro2::TokenStream>> {
+    if sig.asyncness.is_some() || sig.unsafety.is_some() {
+        diagnostic::error(sig.span(), "`dynamic` overloads can't be async or unsafe");
+    }
+    if sig.generics.type_params().next().is_some() || sig.generics.const_params().next().is_some() {
+        diagnostic::error(
+            sig.generics.span(),
+            "`dynamic` overloads can't be generic, they are told apart by the runtime types of \
+             the arguments",
+        );
+    }
+    let mut object: Option<proc_macro2::TokenStream> = None;
+    let mut params = vec![];
+    for input in &sig.inputs {
+        let tp = match input {
+            syn::FnArg::Typed(tp) => tp,
+            syn::FnArg::Receiver(_) => continue,
+        };
+        if !matches!(&*tp.pat, syn::Pat::Ident(_)) {
+            diagnostic::error(
+                tp.pat.span(),
+                "parameters of `dynamic` overloads have to be bound to a name",
+            );
+        }
+        if default_value(&tp.attrs).is_some() || fn_struct::variadic_arity(&tp.attrs).is_some() {
+            diagnostic::error(
+                tp.span(),
+                "`dynamic` overloads can't take default or variadic parameters",
+            );
+        }
+        match &*tp.ty {
+            syn::Type::Reference(reference) if reference.mutability.is_none() => {}
+            ty => diagnostic::error(
+                ty.span(),
+                "parameters of `dynamic` overloads are shared references, `&Type`, or \
+                 `&dyn Trait` to dispatch on",
+            ),
+        }
+        let dispatched = dispatched_object(&tp.ty);
+        if let Some(dispatched) = &dispatched {
+            match &object {
+                Some(object) if object.to_string() != dispatched.to_string() => diagnostic::error(
+                    tp.ty.span(),
+                    format!(
+                        "`dynamic` overloads dispatch on a single trait object type, `{}` \
+                             here",
+                        token_string(object)
+                    ),
+                ),
+                Some(_) => {}
+                None => object = Some(dispatched.clone()),
+            }
+        }
+        params.push(dispatched);
+    }
+    params
+}
+
+// the trait object a `dynamic` set dispatches on, as its `Dispatch` entry point takes it: the one
+// named by `dynamic = Trait`, or else the one `sig`, its first overload, takes
+fn set_object(
+    options: &options::Options,
+    sig: &syn::Signature,
+) -> Option<proc_macro2::TokenStream> {
+    match &options.dispatches {
+        Some(dispatches) => Some(quote!(dyn #dispatches + 'static)),
+        None => sig.inputs.iter().find_map(|input| match input {
+            syn::FnArg::Typed(tp) => dispatched_object(&tp.ty),
+            syn::FnArg::Receiver(_) => None,
+        }),
+    }
+}
+
+// the first overload of a `dynamic` set dispatching on `object`, through which the ranks of the
+// overloads for the arguments in `__overloadf_anys` and `__overloadf_objects` are taken, and the
+// most specific one is called (see `dispatch_entry`)
+fn dispatch_probe(
+    shared_type: &syn::Ident,
+    object: &proc_macro2::TokenStream,
+    output: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    quote!((&&::overloadf::__DispatchProbe::<#shared_type, (#object), #output, 0>(
+        &#shared_type,
+        core::marker::PhantomData,
+    )))
+}
+
+// the statements an overload of a `dynamic` set taking trait objects starts with: they call the
+// most specific overload of the set taking the arguments instead, if that is another one
+fn dispatch_prelude(
+    shared_type: &syn::Ident,
+    index: usize,
+    sig: &syn::Signature,
+    params: &[Option<proc_macro2::TokenStream>],
+) -> Vec<syn::Stmt> {
+    let object = match params.iter().flatten().next() {
+        Some(object) => object,
+        None => return vec![],
+    };
+    let output = match &sig.output {
+        syn::ReturnType::Default => quote!(()),
+        syn::ReturnType::Type(_, ty) => ty.to_token_stream(),
+    };
+    let names: Vec<_> = sig
+        .inputs
+        .iter()
+        .filter_map(|input| match input {
+            syn::FnArg::Typed(tp) => match &*tp.pat {
+                syn::Pat::Ident(pat) => Some(pat.ident.clone()),
+                _ => None,
+            },
+            syn::FnArg::Receiver(_) => None,
+        })
+        .collect();
+    let objects = names.iter().zip(params).map(|(name, param)| match param {
+        Some(_) => quote!(Some(#name)),
+        None => quote!(None),
+    });
+    let probe = dispatch_probe(shared_type, object, &output);
+    let block: syn::Block = parse_quote!({
+        let __overloadf_anys: &[&(dyn core::any::Any + 'static)] = &[#(#names),*];
+        let __overloadf_objects: &[Option<&(#object)>] = &[#(#objects),*];
+        #[allow(unused_imports)]
+        use ::overloadf::{__DispatchFound as _, __DispatchMissing as _};
+        let mut __overloadf_ranks = vec![];
+        #probe.__ranks(__overloadf_anys, __overloadf_objects, &mut __overloadf_ranks);
+        let __overloadf_result = match ::overloadf::__most_specific(&__overloadf_ranks) {
+            Some(__overloadf_index) if __overloadf_index != #index => {
+                #probe.__call(__overloadf_index, __overloadf_anys, __overloadf_objects)
+            }
+            _ => None,
+        };
+        // an overload ranked is one that exists, otherwise this one runs
+        if let Some(__overloadf_result) = __overloadf_result {
+            return __overloadf_result;
+        }
+    });
+    block.stmts
+}
+
+// `Dispatch` of a `dynamic` set whose first overload is `sig`, or an error if the trait object it
+// dispatches on isn't known
+fn set_dispatch(
+    shared_type: &syn::Ident,
+    options: &options::Options,
+    sig: &syn::Signature,
+) -> proc_macro2::TokenStream {
+    match set_object(options, sig) {
+        Some(object) => dispatch_impl(shared_type, &object, sig),
+        None => {
+            diagnostic::error(
+                sig.span(),
+                format!(
+                    "`{}` takes no trait objects to dispatch on, name their trait with \
+                     `#[overload(dynamic = Trait)]`",
+                    sig.ident
+                ),
+            );
+            quote!()
+        }
+    }
+}
+
+// `Dispatch` of a `dynamic` set on `object`, calling the most specific overload like
+// `dispatch_prelude` does, for arguments all of which are trait objects
+fn dispatch_impl(
+    shared_type: &syn::Ident,
+    object: &proc_macro2::TokenStream,
+    sig: &syn::Signature,
+) -> proc_macro2::TokenStream {
+    let output = match &sig.output {
+        syn::ReturnType::Default => quote!(()),
+        syn::ReturnType::Type(_, ty) => ty.to_token_stream(),
+    };
+    let probe = dispatch_probe(shared_type, object, &output);
+    quote!(
+        impl<__A: ::overloadf::__DispatchArgs<#object>> ::overloadf::Dispatch<__A> for #shared_type {
+            type Output = #output;
+            fn dispatch(
+                &self,
+                __overloadf_args: __A,
+            ) -> Result<#output, ::overloadf::OverloadError> {
+                let __overloadf_args = ::overloadf::__DispatchArgs::__objects(&__overloadf_args);
+                let __overloadf_anys: &[&(dyn core::any::Any + 'static)] = &__overloadf_args
+                    .iter()
+                    .map(|__overloadf_arg| *__overloadf_arg as &(dyn core::any::Any + 'static))
+                    .collect::<Vec<_>>();
+                let __overloadf_objects: &[Option<&(#object)>] =
+                    &__overloadf_args.iter().copied().map(Some).collect::<Vec<_>>();
+                #[allow(unused_imports)]
+                use ::overloadf::{__DispatchFound as _, __DispatchMissing as _};
+                let mut __overloadf_ranks = vec![];
+                #probe.__ranks(__overloadf_anys, __overloadf_objects, &mut __overloadf_ranks);
+                ::overloadf::__most_specific(&__overloadf_ranks)
+                    .and_then(|__overloadf_index| {
+                        #probe.__call(__overloadf_index, __overloadf_anys, __overloadf_objects)
+                    })
+                    .ok_or_else(|| ::overloadf::OverloadError {
+                        set: ::overloadf::OverloadSet::name(self),
+                        arguments: __overloadf_anys
+                            .iter()
+                            .map(|__overloadf_any| core::any::Any::type_id(*__overloadf_any))
+                            .collect(),
+                        candidates: ::overloadf::OverloadSet::overloads(self).collect(),
                     })
+            }
+        }
+    )
+}
+
+// the overload after the one at `index` of a `dynamic` set, probed for like the first one (see
+// `dispatch_probe`)
+fn next_dispatch_probe(
+    shared_type: &syn::Ident,
+    index: usize,
+    object: &proc_macro2::TokenStream,
+    output: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let next = index + 1;
+    quote!((&&::overloadf::__DispatchProbe::<#shared_type, #object, #output, #next>(
+        self,
+        core::marker::PhantomData,
+    )))
+}
+
+// `__DispatchEntry` of the overload at `index` of a `dynamic` set dispatching on `set_object`,
+// which ranks it by the arguments and calls it through the set, or passes on to the overloads
+// after it. the entries are implemented for the trait object of the set even where the overload
+// takes none: the next one is probed for in the impl, which has to know the types for it.
+fn dispatch_entry(
+    shared_type: &syn::Ident,
+    index: usize,
+    sig: &syn::Signature,
+    params: &[Option<proc_macro2::TokenStream>],
+    set_object: Option<&proc_macro2::TokenStream>,
+) -> proc_macro2::TokenStream {
+    let output = match &sig.output {
+        syn::ReturnType::Default => quote!(()),
+        syn::ReturnType::Type(_, ty) => ty.to_token_stream(),
+    };
+    let Some(dispatched) = params.iter().flatten().next().or(set_object) else {
+        // reported by `set_dispatch`
+        return dispatch_link(shared_type, index, None, &output);
+    };
+    let object = quote!((#dispatched));
+    let lifetimes: Vec<_> = sig
+        .generics
+        .lifetimes()
+        .map(|l| &l.lifetime.ident)
+        .collect();
+    let count = params.len();
+    let mut rank = 0_u64;
+    let mut checks = vec![];
+    let mut args = vec![];
+    let types = sig.inputs.iter().filter_map(|input| match input {
+        syn::FnArg::Typed(tp) => Some(&*tp.ty),
+        syn::FnArg::Receiver(_) => None,
+    });
+    for (position, (param, ty)) in params.iter().zip(types).enumerate() {
+        match (param, ty) {
+            (Some(_), _) => {
+                checks.push(quote!(__overloadf_objects[#position].is_some()));
+                args.push(quote!(__overloadf_objects[#position].unwrap()));
+            }
+            (None, syn::Type::Reference(reference)) => {
+                rank |= 1 << (count - 1 - position);
+                let referent = static_lifetimes(reference.elem.to_token_stream(), &lifetimes);
+                checks.push(quote!(__overloadf_anys[#position].is::<#referent>()));
+                args.push(quote!(__overloadf_anys[#position].downcast_ref::<#referent>().unwrap()));
+            }
+            // reported by `dynamic_params`
+            (None, _) => return dispatch_link(shared_type, index, Some(dispatched), &output),
+        }
+    }
+    let arity = if count == 0 {
+        quote!(__overloadf_anys.is_empty())
+    } else {
+        quote!(__overloadf_anys.len() == #count)
+    };
+    let call = dispatch_call(quote!(__overloadf_set), quote!((#(#args,)*)));
+    let next = next_dispatch_probe(shared_type, index, &object, &output);
+    quote!(
+        impl ::overloadf::__DispatchEntry<#index, #object, #output> for #shared_type {
+            fn __ranks(
+                &self,
+                __overloadf_anys: &[&(dyn core::any::Any + 'static)],
+                __overloadf_objects: &[Option<&#object>],
+                __overloadf_ranks: &mut Vec<Option<u64>>,
+            ) {
+                #[allow(unused_imports)]
+                use ::overloadf::{__DispatchFound as _, __DispatchMissing as _};
+                __overloadf_ranks.push(if #arity #(&& #checks)* {
+                    Some(#rank)
+                } else {
+                    None
+                });
+                #next.__ranks(__overloadf_anys, __overloadf_objects, __overloadf_ranks)
+            }
+            fn __call(
+                &self,
+                __overloadf_index: usize,
+                __overloadf_anys: &[&(dyn core::any::Any + 'static)],
+                __overloadf_objects: &[Option<&#object>],
+            ) -> Option<#output> {
+                #[allow(unused_imports)]
+                use ::overloadf::{__DispatchFound as _, __DispatchMissing as _};
+                if __overloadf_index != #index {
+                    return #next.__call(__overloadf_index, __overloadf_anys, __overloadf_objects);
                 }
+                let __overloadf_set = *self;
+                Some(#call)
+            }
+        }
+    )
+}
+
+// `__DispatchEntry` at `index` of a `dynamic` set whose overload there was left out: it ranks as
+// taking no arguments, and passes on to the overloads after it. `object` is that of the set, if
+// known (see `dispatch_entry`)
+fn dispatch_link(
+    shared_type: &syn::Ident,
+    index: usize,
+    object: Option<&proc_macro2::TokenStream>,
+    output: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let (generics, object) = match object {
+        Some(object) => (quote!(), quote!((#object))),
+        None => (quote!(<__D: ?Sized>), quote!(__D)),
+    };
+    let next = next_dispatch_probe(shared_type, index, &object, output);
+    quote!(
+        impl #generics ::overloadf::__DispatchEntry<#index, #object, #output> for #shared_type {
+            fn __ranks(
+                &self,
+                __overloadf_anys: &[&(dyn core::any::Any + 'static)],
+                __overloadf_objects: &[Option<&#object>],
+                __overloadf_ranks: &mut Vec<Option<u64>>,
+            ) {
+                #[allow(unused_imports)]
+                use ::overloadf::{__DispatchFound as _, __DispatchMissing as _};
+                __overloadf_ranks.push(None);
+                #next.__ranks(__overloadf_anys, __overloadf_objects, __overloadf_ranks)
+            }
+            fn __call(
+                &self,
+                __overloadf_index: usize,
+                __overloadf_anys: &[&(dyn core::any::Any + 'static)],
+                __overloadf_objects: &[Option<&#object>],
+            ) -> Option<#output> {
+                #[allow(unused_imports)]
+                use ::overloadf::{__DispatchFound as _, __DispatchMissing as _};
+                #next.__call(__overloadf_index, __overloadf_anys, __overloadf_objects)
+            }
+        }
+    )
+}
+
+// the impls of one free function overload, one for each arity its default parameters allow,
+// its part of `__call_dyn`, and the arities. `object` is the trait object a `dynamic` set
+// dispatches on. with `direct`, each arity of a safe const overload is also an inherent const fn
+// of the overloader, which the call macro may call in const contexts (see `call_macro`); its
+// name is given with the arity.
+fn fn_overload(
+    ast: &syn::ItemFn,
+    shared_type: &syn::Ident,
+    index: usize,
+    options: &options::Options,
+    object: Option<&proc_macro2::TokenStream>,
+    direct: bool,
+) -> (
+    proc_macro2::TokenStream,
+    proc_macro2::TokenStream,
+    Vec<(usize, Option<syn::Ident>)>,
+) {
+    let (impl_generics, _ty_generics, where_clause) = ast.sig.generics.split_for_impl();
+    let attrs = &ast.attrs;
+    let dispatched = if options.dynamic {
+        dynamic_params(&ast.sig)
+    } else {
+        vec![]
+    };
+    if let (Some(dispatches), Some(object)) =
+        (&options.dispatches, dispatched.iter().flatten().next())
+    {
+        if object.to_string() != quote!(dyn #dispatches + 'static).to_string() {
+            diagnostic::error(
+                dispatches.span(),
+                format!(
+                    "`{}` dispatches on `{}`, not on `dyn {}`",
+                    ast.sig.ident,
+                    token_string(object),
+                    dispatches
+                ),
             );
-        } else {
-            block = quote!(
-                let #param_assign = args;
-                unsafe {
-                    #(#body)*
+        }
+    }
+    let direct = direct
+        && ast.sig.constness.is_some()
+        && ast.sig.unsafety.is_none()
+        && !options.returns
+        && !options.dynamic;
+    let constness = match (options.dynamic, ast.sig.constness) {
+        (true, Some(constness)) => {
+            diagnostic::warning(
+                constness.span,
+                "calls of `dynamic` overloads are dispatched at runtime, will ignore const",
+            );
+            None
+        }
+        // the call macro takes the const fns, the dispatch trait isn't const
+        _ if direct && backend::stable() => None,
+        _ => const_impl(&ast.sig),
+    };
+    let inputs = &ast.sig.inputs;
+    let output = match &ast.sig.output {
+        syn::ReturnType::Default => quote!(()),
+        syn::ReturnType::Type(_, t) => quote!(#t),
+    };
+    let mut default_values = vec![];
+    let mut input_types = vec![];
+    let mut param_assign = vec![];
+    for tp in inputs.iter() {
+        if let syn::FnArg::Typed(tp) = tp {
+            let assign = default_value(&tp.attrs);
+            let pat: syn::Pat = (*tp.pat).clone();
+            // the trait objects are taken as `dyn Any` too, which needs them to be 'static
+            let ty: syn::Type = match (dispatched.get(input_types.len()), &*tp.ty) {
+                (Some(Some(object)), syn::Type::Reference(reference)) => {
+                    let lifetime = &reference.lifetime;
+                    parse_quote!(& #lifetime (#object))
                 }
+                _ => (*tp.ty).clone(),
+            };
+            input_types.push(ty);
+            param_assign.push(pat);
+            default_values.push(assign);
+        }
+    }
+    let mut body = dispatch_prelude(shared_type, index, &ast.sig, &dispatched);
+    body.extend(ast.block.stmts.iter().cloned());
+    let body = &body;
+    let mut results = vec![];
+    let mut dyn_calls = vec![];
+    let mut arities = vec![];
+    if options.dynamic {
+        if options.returns {
+            diagnostic::error(ast.sig.span(), "`dynamic` can't be combined with `returns`");
+        }
+        if let Some(named) = &options.named {
+            diagnostic::error(named.span(), "`named` can't be combined with `dynamic`");
+        }
+        results.push(dispatch_entry(
+            shared_type,
+            index,
+            &ast.sig,
+            &dispatched,
+            object,
+        ));
+    }
+    if options.returns {
+        if ast.sig.unsafety.is_some() {
+            diagnostic::error(
+                ast.sig.span(),
+                "`returns` overloads are called through `ReturnOverload`, which is safe. unsafe \
+                 overloads can't take `returns`",
+            );
+        }
+        if let Some(named) = &options.named {
+            diagnostic::error(named.span(), "`named` can't be combined with `returns`");
+        }
+        if let (Some(constness), false) = (ast.sig.constness, backend::stable()) {
+            diagnostic::warning(
+                constness.span,
+                "`ReturnOverload` is not a const trait, will ignore const for `returns`",
             );
         }
-    } else if asyncness.is_some() {
-        output = quote!(core::pin::Pin<Box<dyn core::future::Future<Output = #output>>>);
-        block = quote!(
-            let #param_assign = args;
-            Box::pin(async move {
-                #(#body)*
-            })
-        );
-    } else {
-        block = quote!(
-            let #param_assign = args;
-            #(#body)*
-        );
     }
-    let result = fn_impl!(
-        impl_generics,
+    let variadic = variadic_param(&ast.sig);
+    let param_iter = param_variants(
+        &ast.sig,
         input_types,
-        shared_type,
-        where_clause,
-        output,
-        attrs,
-        block
+        param_assign,
+        default_values,
+        variadic,
+        options.returns,
     );
-    result
+    for input_iter::Variant {
+        inputs: input_types,
+        params: param_assign,
+        bindings: defaults,
+        ..
+    } in param_iter
+    {
+        dyn_calls.push(dyn_call(&ast.sig, &input_types, &output, options.returns));
+        let arity = input_types.len();
+        let input_types = quotation_expand!(input_types);
+        let param_assign = quotation_expand!(param_assign);
+        // overloads with `returns` may only differ in their output
+        let alias = if options.returns {
+            future_alias(shared_type, None, &quote!(#input_types #output))
+        } else {
+            future_alias(shared_type, None, &input_types)
+        };
+        let (block, output, opaque) = call_body(
+            &ast.sig,
+            &param_assign,
+            &defaults,
+            body,
+            &output,
+            alias,
+            options,
+        );
+        let result = if options.returns {
+            return_impl!(
+                impl_generics,
+                input_types,
+                shared_type,
+                where_clause,
+                output,
+                attrs,
+                block,
+                opaque
+            )
+        } else if ast.sig.unsafety.is_some() {
+            unsafe_impl!(
+                impl_generics,
+                input_types,
+                shared_type,
+                where_clause,
+                output,
+                attrs,
+                block,
+                opaque
+            )
+        } else {
+            fn_impl!(
+                impl_generics,
+                input_types,
+                shared_type,
+                where_clause,
+                output,
+                attrs,
+                block,
+                opaque,
+                constness
+            )
+        };
+        results.push(result);
+        if direct {
+            let name = format_ident!("__overloadf_const_{}_{}", index, arities.len());
+            results.push(quote!(
+                impl #shared_type {
+                    #[doc(hidden)]
+                    #[inline]
+                    pub const fn #name #impl_generics (self, args: #input_types) -> #output
+                    #where_clause
+                    {
+                        #block
+                    }
+                }
+            ));
+            arities.push((arity, Some(name)));
+        } else {
+            arities.push((arity, None));
+        }
+    }
+    if let (Some(named), false) = (&options.named, options.returns) {
+        results.push(named_overload(ast, named, shared_type, options));
+        arities.push((1, None));
+    }
+    (quote!(#(#results)*), quote!(#(#dyn_calls)*), arities)
 }
 
-fn trait_method_to_fn_trait(
-    trait_path: &syn::Path,
-    tt: &syn::Ident,
-    tp: &syn::Type,
-    ast: &syn::TraitItemFn,
-) -> proc_macro2::TokenStream {
-    let span = ast.span().unstable();
-    if let Some(block) = &ast.default {
-        let (impl_generics, _ty_generics, where_clause) = ast.sig.generics.split_for_impl();
-        let attrs = &ast.attrs;
-        let unsafety = &ast.sig.unsafety;
-        let asyncness = &ast.sig.asyncness;
-        let ident = ast.sig.ident.clone();
-        let shared_type = format_ident!("Overloader_{}_{}", tt, ident);
-        let inputs = &ast.sig.inputs;
-        let mut output = get_output(&ast.sig.output, tp);
-        let mut input_types = Vec::<syn::Type>::new();
-        let mut input_params = vec![];
-        let mut param_assign = Vec::<syn::Pat>::new();
-        for (i, itp) in inputs.iter().enumerate() {
-            input_params.push(format_ident!("_{}", i));
-            match itp {
-                syn::FnArg::Typed(itp) => {
-                    let ty: syn::Type = Box::leak(itp.ty.clone()).clone();
-                    let ty = if let syn::Type::Path(path) = ty.clone() {
-                        if path.path.segments.len() == 1 {
-                            replace_self(ty, trait_path).unwrap()
-                        } else {
-                            replace_self(ty, quote!(<#tp as #trait_path>)).unwrap()
-                        }
-                    } else {
-                        ty
-                    };
-                    input_types.push(ty);
-                    param_assign.push(Box::leak(itp.pat.clone()).clone());
-                }
-                syn::FnArg::Receiver(r) => {
-                    let ty = match (r.reference.as_ref(), r.mutability.as_ref()) {
-                        (Some(_), Some(_)) => quote!(&mut #tp),
-                        (Some(_), None) => quote!(&#tp),
-                        (None, Some(_)) => quote!(mut #tp),
-                        (None, None) => quote!(#tp),
-                    };
-                    let id: syn::Ident = syn::parse_str("__self").unwrap();
-                    let pid = syn::PatIdent {
-                        attrs: vec![],
-                        by_ref: None,
-                        mutability: None,
-                        ident: id,
-                        subpat: None,
-                    };
-                    input_types.push(syn::parse_str(&ty.to_string()).unwrap());
-                    param_assign.push(pid.into());
+// names the elided lifetimes of a type, `&'_ T` and `&T` become `&'lifetime T`. returns whether
+// there were any
+fn name_lifetimes(
+    input: proc_macro2::TokenStream,
+    lifetime: &syn::Lifetime,
+) -> (proc_macro2::TokenStream, bool) {
+    use proc_macro2::{Punct, Spacing, TokenTree};
+    let mut output = proc_macro2::TokenStream::new();
+    let mut named = false;
+    let mut trees = input.into_iter().peekable();
+    while let Some(tree) = trees.next() {
+        match tree {
+            TokenTree::Punct(p) if p.as_char() == '&' => {
+                output.extend(Some(TokenTree::Punct(Punct::new('&', Spacing::Alone))));
+                if !matches!(trees.peek(), Some(TokenTree::Punct(p)) if p.as_char() == '\'') {
+                    output.extend(quote!(#lifetime));
+                    named = true;
+                }
+            }
+            TokenTree::Punct(p) if p.as_char() == '\'' => match trees.peek() {
+                Some(TokenTree::Ident(ident)) if ident == "_" => {
+                    trees.next();
+                    output.extend(quote!(#lifetime));
+                    named = true;
                 }
+                _ => output.extend(Some(TokenTree::Punct(p))),
+            },
+            TokenTree::Group(group) => {
+                let (stream, inner) = name_lifetimes(group.stream(), lifetime);
+                let mut replaced = proc_macro2::Group::new(group.delimiter(), stream);
+                replaced.set_span(group.span());
+                output.extend(Some(TokenTree::Group(replaced)));
+                named |= inner;
             }
+            tree => output.extend(Some(tree)),
         }
-        let new_block: syn::Block = replace_self(block, tp).unwrap();
-        let body = &new_block.stmts;
-        let param_assign = quotation_expand!(param_assign);
-        let input_types = quotation_expand!(input_types);
-        let block;
-        if unsafety.is_some() {
-            span.warning(
-                "unsafe fn is not supported. ".to_owned()
-                    + "Will wrap in a unsafe block to make function safe.",
-            )
-            .emit();
-            if asyncness.is_some() {
-                output = quote!(core::pin::Pin<Box<dyn core::future::Future<Output = #output>>>);
-                block = quote!(
-                    let #param_assign = args;
-                    unsafe {
-                        Box::pin(async move {
-                            #(#body)*
-                        })
-                    }
-                );
-            } else {
-                block = quote!(
-                    let #param_assign = args;
-                    unsafe {
-                        #(#body)*
-                    }
+    }
+    (output, named)
+}
+
+// `#[overload(named = XddArgs)]`: the parameters of the overload as a struct, made by `new` from
+// the parameters without default (or by `Default` when all have one), with a setter for each
+// parameter. the set takes it in place of the positional arguments, so that any default parameter
+// can be left out, and a misspelled name is a missing method.
+fn named_overload(
+    ast: &syn::ItemFn,
+    named: &syn::Ident,
+    shared_type: &syn::Ident,
+    options: &options::Options,
+) -> proc_macro2::TokenStream {
+    use syn::ext::IdentExt;
+    let vis = &ast.vis;
+    // fields can't elide lifetimes, they share one instead
+    let lifetime: syn::Lifetime = parse_quote!('overloadf);
+    let mut elided = false;
+    let mut fields = vec![];
+    let mut types = vec![];
+    let mut pats = vec![];
+    let mut required = vec![];
+    let mut values = vec![];
+    for input in &ast.sig.inputs {
+        let syn::FnArg::Typed(tp) = input else {
+            continue;
+        };
+        if tp.attrs.iter().any(|attr| attr.path().is_ident("variadic")) {
+            diagnostic::error(tp.span(), "named arguments can't take a variadic parameter");
+            return quote!();
+        }
+        let field = match &*tp.pat {
+            syn::Pat::Ident(p) if p.by_ref.is_none() && p.subpat.is_none() => p.ident.clone(),
+            pat => {
+                diagnostic::error(
+                    pat.span(),
+                    "named arguments take their names from the parameters, which have to be \
+                     plain identifiers",
                 );
+                return quote!();
             }
-        } else if asyncness.is_some() {
-            output = quote!(core::pin::Pin<Box<dyn core::future::Future<Output = #output>>>);
-            block = quote!(
-                let #param_assign = args;
-                Box::pin(async move {
-                    #(#body)*
-                })
-            );
-        } else {
-            block = quote!(
-                let #param_assign = args;
-                #(#body)*
+        };
+        // the setters are named like the parameters, next to the constructor
+        if field.unraw() == "new" {
+            diagnostic::error(
+                field.span(),
+                format!(
+                    "`{}::new` makes the named arguments of `{}`, rename the parameter `new`",
+                    named, ast.sig.ident
+                ),
             );
+            return quote!();
+        }
+        let (ty, named) = name_lifetimes(tp.ty.to_token_stream(), &lifetime);
+        let ty: syn::Type = syn::parse2(ty).unwrap();
+        elided |= named;
+        match default_value(&tp.attrs) {
+            Some(assign) => {
+                let value = &assign.right;
+                values.push(quote!(let #field: #ty = #value;));
+            }
+            None => required.push(quote!(#field: #ty)),
+        }
+        fields.push(field);
+        types.push(ty);
+        pats.push((*tp.pat).clone());
+    }
+    // a setter named `default` would be found before `Default::default`
+    if let Some(field) = fields
+        .iter()
+        .find(|field| required.is_empty() && field.unraw() == "default")
+    {
+        diagnostic::error(
+            field.span(),
+            format!(
+                "`{}::default` makes the named arguments of `{}`, rename the parameter `default`",
+                named, ast.sig.ident
+            ),
+        );
+        return quote!();
+    }
+    let mut generics = ast.sig.generics.clone();
+    if elided {
+        generics.params.insert(0, parse_quote!(#lifetime));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let default = if required.is_empty() {
+        quote!(
+            impl #impl_generics Default for #named #ty_generics #where_clause {
+                fn default() -> Self {
+                    Self::new()
+                }
+            }
+        )
+    } else {
+        quote!()
+    };
+    let doc = format!("Named arguments of `{}`.", ast.sig.ident);
+    let field_docs = fields
+        .iter()
+        .map(|field| format!("The `{}` argument.", field));
+    let setter_docs = fields
+        .iter()
+        .map(|field| format!("Sets the `{}` argument.", field));
+    let declaration = quote!(
+        #[doc = #doc]
+        #vis struct #named #impl_generics #where_clause {
+            #(
+                #[doc = #field_docs]
+                #vis #fields: #types,
+            )*
+        }
+        impl #impl_generics #named #ty_generics #where_clause {
+            /// Takes the arguments without default, the others start at their default.
+            #vis fn new(#(#required),*) -> Self {
+                #(#values)*
+                Self { #(#fields),* }
+            }
+            #(
+                #[doc = #setter_docs]
+                #vis fn #fields(mut self, #fields: #types) -> Self {
+                    self.#fields = #fields;
+                    self
+                }
+            )*
         }
-        let result = fn_impl!(
+        #default
+    );
+    let input_types = quote!((#named #ty_generics,));
+    // only plain bindings get here, which need no field names
+    let param_assign = quote!((#named { #(#pats),* },));
+    let output = match &ast.sig.output {
+        syn::ReturnType::Default => quote!(()),
+        syn::ReturnType::Type(_, t) => quote!(#t),
+    };
+    let alias = future_alias(shared_type, None, &input_types);
+    let (block, output, opaque) = call_body(
+        &ast.sig,
+        &param_assign,
+        &[],
+        &ast.block.stmts,
+        &output,
+        alias,
+        options,
+    );
+    let attrs = &ast.attrs;
+    let constness = const_impl(&ast.sig);
+    let result = if ast.sig.unsafety.is_some() {
+        unsafe_impl!(
             impl_generics,
             input_types,
             shared_type,
@@ -539,402 +3611,640 @@ fn trait_method_to_fn_trait(
             output,
             attrs,
             block,
-            tp
-        );
-        return result;
+            opaque
+        )
     } else {
-        span.error(format!(
-            "trait function {} with empty default",
-            ast.into_token_stream().to_string()
-        ))
-        .emit();
-    }
-    quote!()
+        fn_impl!(
+            impl_generics,
+            input_types,
+            shared_type,
+            where_clause,
+            output,
+            attrs,
+            block,
+            opaque,
+            constness
+        )
+    };
+    quote!(
+        #declaration
+        #result
+    )
 }
 
-fn impl_method_to_fn_trait(
-    trait_path: &syn::Path,
-    tt: &syn::Ident,
-    tp: &syn::Type,
-    ast: &syn::ImplItemFn,
-) -> proc_macro2::TokenStream {
-    let span = ast.span().unstable();
-    let (impl_generics, _ty_generics, where_clause) = ast.sig.generics.split_for_impl();
-    let attrs = &ast.attrs;
-    let unsafety = &ast.sig.unsafety;
-    let asyncness = &ast.sig.asyncness;
-    let ident = ast.sig.ident.clone();
-    let shared_type = format_ident!("Overloader_{}_{}", tt, ident);
-    let inputs = &ast.sig.inputs;
-    let mut output = get_output(&ast.sig.output, tp);
-    let mut input_types = vec![];
-    let mut input_params = vec![];
-    let mut param_assign = vec![];
-    for (i, itp) in inputs.iter().enumerate() {
-        match itp {
-            syn::FnArg::Typed(itp) => {
-                let pat: syn::Pat = Box::leak(itp.pat.clone()).clone();
-                let ty: syn::Type = Box::leak(itp.ty.clone()).clone();
-                let ty = if let syn::Type::Path(path) = ty.clone() {
-                    if path.path.segments.len() == 1 {
-                        replace_self(ty, trait_path).unwrap()
-                    } else {
-                        replace_self(ty, quote!(<#tp as #trait_path>)).unwrap()
-                    }
-                } else {
-                    ty
+// the use of a deprecated item, spanned at the overload so that the deprecation warning of
+// `#[overload] fn` is reported there. the lint attributes of the overload apply to it, so that
+// `#[allow(deprecated)]` on the overload or around it silences it
+fn deprecated_fn(ast: &syn::ItemFn) -> proc_macro2::TokenStream {
+    let span = ast.sig.ident.span();
+    let lints = ast.attrs.iter().filter(|attr| {
+        ["allow", "expect", "warn", "deny", "forbid"]
+            .iter()
+            .any(|lint| attr.path().is_ident(lint))
+    });
+    quote_spanned!(span=>
+        #(#lints)*
+        const _: () = {
+            #[deprecated(
+                note = "`#[overload]` on a function is deprecated: declare the overloads of a \
+                        set together in `#[overload] mod` or `overload_set!`"
+            )]
+            struct OverloadFn;
+            let _ = OverloadFn;
+        };
+    )
+}
+
+fn process_fn(ast: syn::ItemFn, options: &options::Options) -> TokenStream {
+    let vis = &ast.vis;
+    let ident = &ast.sig.ident;
+    let shared_type = format_ident!("Overloader_{}", ident);
+    let site = proc_macro::Span::call_site();
+    let overload = (
+        format!("{}:{}", site.line(), site.column()),
+        ast.sig.to_token_stream().to_string(),
+    );
+    let info = SetInfo {
+        unsafety: ast.sig.unsafety.is_some(),
+        returns: options.returns,
+        dynamic: options.dynamic,
+        stable: options.stable,
+        object: options
+            .dynamic
+            .then(|| set_object(options, &ast.sig))
+            .flatten()
+            .map(|object| object.to_string()),
+        overloads: vec![overload.clone()],
+    };
+    let key = format!("{}::{}", site.file(), ident);
+    let (index, defined) = {
+        let mut namings = NAMINGS.lock().unwrap();
+        let set = namings.entry(key).or_insert_with(|| info.clone());
+        let index = set
+            .overloads
+            .iter()
+            .position(|o| *o == overload)
+            .or_else(|| {
+                set.overloads
+                    .iter()
+                    .position(|(_, signature)| *signature == overload.1)
+            });
+        match index {
+            Some(0) => {
+                let overloads = std::mem::take(&mut set.overloads);
+                *set = SetInfo {
+                    overloads,
+                    ..info.clone()
                 };
-                input_params.push(format_ident!("_{}", i));
-                input_types.push(ty);
-                param_assign.push(pat);
+                set.overloads[0] = overload;
+                (0, None)
             }
-            syn::FnArg::Receiver(r) => {
-                input_params.push(format_ident!("_{}", i));
-                let ty = match (r.reference.as_ref(), r.mutability.as_ref()) {
-                    (Some(_), Some(_)) => quote!(&mut #tp),
-                    (Some(_), None) => quote!(&#tp),
-                    (None, Some(_)) => quote!(mut #tp),
-                    (None, None) => quote!(#tp),
-                };
-                let id: syn::Ident = syn::parse_str("__self").unwrap();
-                let pid = syn::PatIdent {
-                    attrs: vec![],
-                    by_ref: None,
-                    mutability: None,
-                    ident: id,
-                    subpat: None,
-                };
-                input_types.push(syn::parse_str(&ty.to_string()).unwrap());
-                param_assign.push(pid.into());
+            Some(index) => {
+                set.overloads[index] = overload;
+                (index, Some(set.clone()))
+            }
+            None => {
+                set.overloads.push(overload);
+                (set.overloads.len() - 1, Some(set.clone()))
             }
         }
+    };
+    if let Some(set) = &defined {
+        if set.unsafety != info.unsafety {
+            mixed_safety(&ast.sig);
+        }
+        if set.returns != info.returns {
+            mixed_returns(&ast.sig);
+        }
+        if set.dynamic != info.dynamic {
+            mixed_dynamic(&ast.sig);
+        } else if set.dynamic {
+            dynamic_output(&ast.sig, &syn::parse_str(&set.overloads[0].1).unwrap());
+        }
+        if set.stable != info.stable {
+            mixed_backends(&ast.sig);
+        }
     }
-    let new_block: syn::Block = replace_self(&ast.block, tp).unwrap();
-    let body = &new_block.stmts;
-    let param_assign = quotation_expand!(param_assign);
-    let input_types = quotation_expand!(input_types);
-    let block;
-    if unsafety.is_some() {
-        span.warning(
-            "unsafe fn is not supported. ".to_owned()
-                + "Will wrap in a unsafe block to make function safe.",
-        )
-        .emit();
-        if asyncness.is_some() {
-            output = quote!(core::pin::Pin<Box<dyn core::future::Future<Output = #output>>>);
-            block = quote!(
-                let #param_assign = args;
-                unsafe {
-                    Box::pin(async move {
-                        #(#body)*
-                    })
-                }
-            );
+    let conflicting = defined
+        .as_ref()
+        .is_some_and(|set| check_earlier_overlaps(&ast.sig, &set.overloads[..index], set.returns));
+    let prepare = if defined.is_none() {
+        // the later overloads aren't expanded yet
+        let intro = format!(
+            "Overloaded function. Its overloads are expanded one at a time, so only the first \
+             one is listed here. **Every overload is listed on the rustdoc page of [`{}`].**",
+            shared_type
+        );
+        let docs = set_docs(&intro, vec![(&ast.sig, &ast.attrs[..])]);
+        let declaration = set_declaration(vis, ident, &shared_type, docs);
+        let reflection = set_reflection(
+            &shared_type,
+            &syn::Generics::default(),
+            &ident.to_string(),
+            probed_overloads(&shared_type),
+        );
+        let dynamic = set_dynamic(
+            &shared_type,
+            &syn::Generics::default(),
+            &syn::Generics::default(),
+            probed_dyn_calls(&shared_type),
+        );
+        let dispatch = if options.dynamic {
+            set_dispatch(&shared_type, options, &ast.sig)
         } else {
-            block = quote!(
-                let #param_assign = args;
-                unsafe {
-                    #(#body)*
-                }
+            quote!()
+        };
+        quote!(
+            #declaration
+            #reflection
+            #dynamic
+            #dispatch
+        )
+    } else {
+        quote!()
+    };
+    let mut call_macro = if options.call_macro {
+        let returns = defined.as_ref().map_or(options.returns, |set| set.returns);
+        call_macro(vis, ident, options, returns, &[])
+    } else {
+        quote!()
+    };
+    call_macro.extend(deprecated_fn(&ast));
+    let object = match &defined {
+        Some(set) => set.object.as_ref().map(|object| object.parse().unwrap()),
+        None => info.object.as_ref().map(|object| object.parse().unwrap()),
+    };
+    if conflicting {
+        // the conflicting impls are left out, only the error is reported
+        let mut entry = overload_entry(&shared_type, index, None, quote!());
+        if options.dynamic {
+            let output = match &ast.sig.output {
+                syn::ReturnType::Default => quote!(()),
+                syn::ReturnType::Type(_, ty) => ty.to_token_stream(),
+            };
+            entry.extend(dispatch_link(&shared_type, index, object.as_ref(), &output));
+        }
+        return quote!(
+            #prepare
+            #call_macro
+            #entry
+        )
+        .into();
+    }
+    let (overload, dyn_call, _) =
+        fn_overload(&ast, &shared_type, index, options, object.as_ref(), false);
+    let entry = overload_entry(&shared_type, index, Some(&ast.sig), dyn_call);
+    let doc = overload_impl_doc(&shared_type, &ast.sig, &ast.attrs);
+    quote!(
+        #prepare
+        #call_macro
+        #entry
+        #doc
+        #overload
+    )
+    .into()
+}
+
+// a whole set of free function overloads, as seen by `#[overload] mod` and `overload_set!`. the
+// set takes the visibility of its first overload.
+fn grouped_set(
+    ident: &syn::Ident,
+    overloads: &[(syn::ItemFn, options::Options)],
+) -> proc_macro2::TokenStream {
+    backend::with(overloads[0].1.stable, || {
+        grouped_set_items(ident, overloads)
+    })
+}
+
+fn grouped_set_items(
+    ident: &syn::Ident,
+    overloads: &[(syn::ItemFn, options::Options)],
+) -> proc_macro2::TokenStream {
+    let shared_type = format_ident!("Overloader_{}", ident);
+    let vis = &overloads[0].0.vis;
+    let unsafety = overloads[0].0.sig.unsafety.is_some();
+    let returns = overloads[0].1.returns;
+    let dynamic = overloads[0].1.dynamic;
+    let duplicates = check_overlaps(overloads.iter().map(|(item_fn, _)| &item_fn.sig), returns);
+    let listed: Vec<_> = overloads
+        .iter()
+        .zip(&duplicates)
+        .filter(|(_, duplicate)| !**duplicate)
+        .map(|((item_fn, _), _)| item_fn)
+        .collect();
+    let docs = set_docs(
+        "Overloaded function, with the overloads:",
+        listed
+            .iter()
+            .map(|item_fn| (&item_fn.sig, &item_fn.attrs[..])),
+    );
+    let mut results = vec![
+        set_declaration(vis, ident, &shared_type, docs),
+        set_reflection(
+            &shared_type,
+            &syn::Generics::default(),
+            &ident.to_string(),
+            listed_overloads(listed.iter().map(|item_fn| &item_fn.sig)),
+        ),
+    ];
+    // the call macro takes the path given to any overload
+    let macro_options = overloads
+        .iter()
+        .find(|(_, options)| options.macro_path.is_some())
+        .or_else(|| overloads.iter().find(|(_, options)| options.call_macro))
+        .map(|(_, options)| options);
+    // the trait named on any overload, or else the one the first overload taking trait objects
+    // dispatches on
+    let dispatch = dynamic.then(|| {
+        let mut options = overloads[0].1.clone();
+        options.dispatches = overloads
+            .iter()
+            .find_map(|(_, options)| options.dispatches.clone());
+        let sig = overloads
+            .iter()
+            .map(|(item_fn, _)| &item_fn.sig)
+            .find(|sig| set_object(&options, sig).is_some())
+            .unwrap_or(&overloads[0].0.sig);
+        (options, sig)
+    });
+    let object = dispatch
+        .as_ref()
+        .and_then(|(options, sig)| set_object(options, sig));
+    let mut dyn_calls = vec![];
+    let mut arities = vec![];
+    for ((item_fn, options), duplicate) in overloads.iter().zip(duplicates) {
+        if item_fn.sig.unsafety.is_some() != unsafety {
+            mixed_safety(&item_fn.sig);
+        }
+        if options.returns != returns {
+            mixed_returns(&item_fn.sig);
+        }
+        if options.dynamic != dynamic {
+            mixed_dynamic(&item_fn.sig);
+        } else if dynamic {
+            dynamic_output(&item_fn.sig, &overloads[0].0.sig);
+        }
+        if options.stable != backend::stable() {
+            mixed_backends(&item_fn.sig);
+        }
+        if !duplicate {
+            let index = dyn_calls.len();
+            let (overload, dyn_call, overload_arities) = fn_overload(
+                item_fn,
+                &shared_type,
+                index,
+                options,
+                object.as_ref(),
+                macro_options.is_some(),
             );
+            arities.extend(overload_arities);
+            results.push(overload_impl_doc(
+                &shared_type,
+                &item_fn.sig,
+                &item_fn.attrs,
+            ));
+            results.push(overload);
+            dyn_calls.push(dyn_call);
         }
-    } else if asyncness.is_some() {
-        output = quote!(core::pin::Pin<Box<dyn core::future::Future<Output = #output>>>);
-        block = quote!(
-            let #param_assign = args;
-            Box::pin(async move {
-                #(#body)*
-            })
-        );
+    }
+    results.push(set_dynamic(
+        &shared_type,
+        &syn::Generics::default(),
+        &syn::Generics::default(),
+        quote!(#(#dyn_calls)* None),
+    ));
+    if let Some(options) = macro_options {
+        results.push(call_macro(vis, ident, options, returns, &arities));
+    }
+    if let Some((options, sig)) = dispatch {
+        results.push(set_dispatch(&shared_type, &options, sig));
+    }
+    quote!(#(#results)*)
+}
+
+// signatures are duplicates if this is equal. with `returns`, those with different return types
+// are not
+fn duplicate_key(sig: &syn::Signature, returns: bool) -> String {
+    let mut normalized = sig.clone();
+    normalized.constness = None;
+    normalized.asyncness = None;
+    normalized.unsafety = None;
+    let key = sig_normalize(&normalized);
+    match &sig.output {
+        syn::ReturnType::Type(_, ty) if returns => format!("{} -> {}", key, ty.to_token_stream()),
+        _ => key,
+    }
+}
+
+fn set_overload(sig: &syn::Signature, returns: bool) -> overlap::Overload {
+    let overload = overlap::Overload::new(sig);
+    if returns {
+        overload.returning(sig)
     } else {
-        block = quote!(
-            let #param_assign = args;
-            #(#body)*
-        );
+        overload
     }
-    fn_impl!(
-        impl_generics,
-        input_types,
-        shared_type,
-        where_clause,
-        output,
-        attrs,
-        block,
-        tp
+}
+
+fn overlap_message(ident: &syn::Ident, overlap: &overlap::Overlap) -> String {
+    format!(
+        "overloads of `{}` overlap: both take `{}`{}{}",
+        ident,
+        overlap.arguments,
+        match &overlap.output {
+            Some(output) => format!(" and return `{}`", output),
+            None => String::new(),
+        },
+        if overlap.defaults {
+            " once default parameters are left out"
+        } else {
+            ""
+        }
     )
 }
 
-fn process_impl(mut item: syn::ItemImpl) -> TokenStream {
-    let self_type = Box::leak(item.self_ty.clone());
-    let span = item.span().unstable();
-    let mut generated = vec![];
-    let mut items = vec![];
-    if let Some((_, path, _)) = item.trait_.clone() {
-        // impl Trait for Struct {}
-        if let Some(pathseg) = path.segments.first() {
-            let ident = &pathseg.ident;
-            if let Some(shared_fields) = TRAIT_IDENTS.lock().unwrap().get(&ident.to_string()) {
-                let mut set = HashSet::new();
-                for i in &item.items {
-                    if let syn::ImplItem::Fn(item_method) = i {
-                        let method_id = item_method.sig.ident.to_string();
-                        let method_sig = sig_normalize(&item_method.sig);
-                        set.insert(method_sig);
-                        if shared_fields.iter().any(|e| e == &method_id) {
-                            generated.push(impl_method_to_fn_trait(
-                                &path,
-                                ident,
-                                self_type,
-                                item_method,
-                            ));
-                        } else {
-                            items.push(syn::ImplItem::Fn(item_method.clone()));
-                        }
+// overloads that the Fn* impls can't tell apart (see `overlap`), reported at both of them.
+// returns for each overload whether it repeats or overlaps an earlier one, so that its
+// conflicting impls can be left out.
+fn check_overlaps<'a, I: IntoIterator<Item = &'a syn::Signature>>(
+    sigs: I,
+    returns: bool,
+) -> Vec<bool> {
+    let mut seen: Vec<(String, &syn::Signature, overlap::Overload)> = vec![];
+    let mut conflicts = vec![];
+    for sig in sigs {
+        let key = duplicate_key(sig, returns);
+        let overload = set_overload(sig, returns);
+        let mut conflicting = false;
+        for (first_key, first, first_overload) in &seen {
+            if *first_key == key {
+                diagnostic::error_with_note(
+                    sig.span(),
+                    format!("duplicate overload of `{}`", sig.ident),
+                    first.span(),
+                    if returns {
+                        "the same parameter and return types are those of this one"
                     } else {
-                        items.push(i.clone());
-                    }
-                }
-                // all trait function definitions
-                if let Some(map) = DEFAULT_DEFINITION.lock().unwrap().get(&ident.to_string()) {
-                    for (k, v) in map.iter() {
-                        // check if is not implemented
-                        if !set.contains(k) {
-                            let item_method: syn::TraitItemFn = syn::parse_str(v).unwrap();
-                            let method_id = item_method.sig.ident.to_string();
-                            // check if method needs overloading
-                            if shared_fields.iter().any(|e| e == &method_id) {
-                                generated.push(trait_method_to_fn_trait(
-                                    &path,
-                                    ident,
-                                    self_type,
-                                    &item_method,
-                                ));
-                            }
-                        }
-                    }
-                } else {
-                    span.error("definition of trait methods not found").emit();
-                }
-            } else {
-                span.error("definition of trait not found").emit();
+                        "the same parameter types are taken here"
+                    },
+                );
+                conflicting = true;
+                break;
             }
-        } else {
-            span.error("complex trait path (including colon) is not yet supported.")
-                .emit();
-        }
-    } else {
-        // normal impl Struct {}
-        let mut fn_names = HashSet::new();
-        let mut dup = HashSet::new();
-        let mut undefined = HashSet::new();
-        for i in &item.items {
-            if let syn::ImplItem::Fn(item_method) = i {
-                let method_id = item_method.sig.ident.to_string();
-                if !fn_names.insert(method_id.clone()) {
-                    dup.insert(method_id);
-                }
+            if let Some(overlap) = first_overload.overlap(&overload) {
+                diagnostic::error_with_note(
+                    sig.span(),
+                    overlap_message(&sig.ident, &overlap),
+                    first.span(),
+                    "overlapping overload",
+                );
+                conflicting = true;
+                break;
             }
         }
-        for i in &item.items {
-            if let syn::ImplItem::Fn(item_method) = i {
-                let vis = &item_method.vis;
-                let method_id = item_method.sig.ident.to_string();
-                if dup.get(&method_id).is_some() {
-                    if undefined.insert(method_id.clone()) {
-                        let const_field = &item_method.sig.ident;
-                        let tp_str = self_type.into_token_stream().to_string().replace(' ', "_");
-                        let shared_type = format_ident!("Overloader_{}_{}", tp_str, method_id);
-                        let const_stream: TokenStream = quote!(
-                            #[allow(non_upper_case_globals)]
-                            const #const_field: #shared_type = #shared_type;
-                        )
-                        .into();
-                        let t = syn::parse_macro_input!(const_stream as syn::ImplItemConst);
-                        items.push(syn::ImplItem::Const(t));
-                        generated.push(quote!(
-                            #[allow(non_camel_case_types, dead_code)]
-                            #[derive(Copy, Clone)]
-                            #vis struct #shared_type;
-                            unsafe impl Send for #shared_type {}
-                            unsafe impl Sync for #shared_type {}
-                        ));
-                    }
-                    generated.push(impl_method_to_non_trait(self_type, item_method));
-                } else {
-                    items.push(syn::ImplItem::Fn(item_method.clone()));
+        if !conflicting {
+            seen.push((key, sig, overload));
+        }
+        conflicts.push(conflicting);
+    }
+    conflicts
+}
+
+// `check_overlaps` for an `#[overload] fn`, against the overloads of its set expanded before it.
+// only their place and signature are known by then, so the error is reported at this overload
+// and names the other one. an overload at the same place is either generated by the same
+// `macro_rules!` invocation or an earlier expansion of this one, and is left to rustc.
+fn check_earlier_overlaps(
+    sig: &syn::Signature,
+    earlier: &[(String, String)],
+    returns: bool,
+) -> bool {
+    let site = proc_macro::Span::call_site();
+    let place = format!("{}:{}", site.line(), site.column());
+    let key = duplicate_key(sig, returns);
+    let overload = set_overload(sig, returns);
+    for (_, signature) in earlier.iter().filter(|(other, _)| *other != place) {
+        let Ok(first) = syn::parse_str::<syn::Signature>(signature) else {
+            continue;
+        };
+        let message = if duplicate_key(&first, returns) == key {
+            format!("duplicate overload of `{}`", sig.ident)
+        } else if let Some(overlap) = set_overload(&first, returns).overlap(&overload) {
+            overlap_message(&sig.ident, &overlap)
+        } else {
+            continue;
+        };
+        diagnostic::error(
+            sig.span(),
+            format!(
+                "{}. the other overload is `{}`",
+                message,
+                token_string(&first)
+            ),
+        );
+        return true;
+    }
+    false
+}
+
+// `overload_set! { .. }`: a group of free functions that are all overloaded, with default values
+// written inline (`a: i32 = 5`). options go in `#[overload(..)]` on the functions.
+fn process_set(input: OverloadSet) -> TokenStream {
+    let mut sets: Vec<(syn::Ident, Vec<(syn::ItemFn, options::Options)>)> = vec![];
+    for item_fn in input.0 {
+        let mut item_fn: syn::ItemFn = item_fn.into();
+        let mut options = options::Options::default();
+        for attr in item_fn.attrs.iter().filter(|attr| is_overload_attr(attr)) {
+            if let syn::Meta::List(list) = &attr.meta {
+                match list.parse_args::<options::Options>() {
+                    Ok(o) => options.merge(&o),
+                    Err(e) => diagnostic::error(e.span(), e.to_string()),
                 }
-            } else {
-                items.push(i.clone());
             }
         }
+        item_fn.attrs.retain(|attr| !is_overload_attr(attr));
+        match sets
+            .iter_mut()
+            .find(|(ident, _)| ident == &item_fn.sig.ident)
+        {
+            Some((_, overloads)) => overloads.push((item_fn, options)),
+            None => sets.push((item_fn.sig.ident.clone(), vec![(item_fn, options)])),
+        }
     }
-    item.items = items;
-    let result = quote!(
-        #(#generated)*
-        #item
-    );
-    result.into()
+    let sets = sets
+        .iter()
+        .map(|(ident, overloads)| grouped_set(ident, overloads));
+    quote!(#(#sets)*).into()
 }
 
-fn param_variants(
-    input_types: Vec<syn::Type>,
-    param_assign: Vec<syn::Pat>,
-    default_values: Vec<Option<fn_struct::Assign>>,
-) -> input_iter::InputIter {
-    input_iter::InputIter::new(input_types, param_assign, default_values)
+struct OverloadSet(Vec<fn_struct::ItemFn>);
+
+impl syn::parse::Parse for OverloadSet {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut items = vec![];
+        while !input.is_empty() {
+            items.push(input.parse()?);
+        }
+        Ok(Self(items))
+    }
 }
 
-fn process_fn(ast: syn::ItemFn) -> TokenStream {
-    let span = ast.span().unstable();
-    let (impl_generics, _ty_generics, where_clause) = ast.sig.generics.split_for_impl();
-    let attrs = ast.attrs;
-    let vis = ast.vis;
-    let constness = ast.sig.constness;
-    let unsafety = ast.sig.unsafety;
-    let asyncness = ast.sig.asyncness;
-    let ident = ast.sig.ident.clone();
-    let inputs = ast.sig.inputs;
-    let mut output = match ast.sig.output {
-        syn::ReturnType::Default => quote!(()),
-        syn::ReturnType::Type(_, t) => quote!(#t),
+fn is_overload_attr(attr: &syn::Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "overload")
+}
+
+// `#[overload] mod`: every function of the module that shares its name with another one, or has
+// default parameters, or is marked `#[overload(..)]` itself, becomes an overload. the sets are
+// known up front, so nothing is kept between expansions, and the generated items stay inside the
+// module, in the order of the first overload of each set. options given to a function are added
+// to the ones of the module.
+// a function of a `#[overload] mod`, with its options and whether it's marked `#[overload]`
+type ModFn = (syn::ItemFn, options::Options, bool);
+
+fn process_mod(mut item: syn::ItemMod, options: &options::Options) -> TokenStream {
+    let span = item.span();
+    let Some((brace, content)) = item.content.take() else {
+        diagnostic::error(span, "overload needs a module with a body");
+        return quote!(#item).into();
     };
-    let shared_type = format_ident!("Overloader_{}", ident);
-    let mut default_values = vec![];
-    let mut input_types = vec![];
-    let mut param_assign = vec![];
-    for tp in inputs.iter() {
-        if let syn::FnArg::Typed(tp) = tp {
-            let mut assign: Option<fn_struct::Assign> = None;
-            for attr in &tp.attrs {
-                if attr.path().is_ident("default") {
-                    assign = Some(attr.parse_args().unwrap());
-                    break;
+    let mut sets: Vec<(syn::Ident, Vec<ModFn>)> = vec![];
+    let mut items = vec![];
+    for i in content {
+        let syn::Item::Fn(mut item_fn) = i else {
+            items.push(Some(i));
+            continue;
+        };
+        let mut fn_options = options.clone();
+        let mut marked = false;
+        for attr in item_fn.attrs.iter().filter(|attr| is_overload_attr(attr)) {
+            marked = true;
+            if let syn::Meta::List(list) = &attr.meta {
+                match list.parse_args::<options::Options>() {
+                    Ok(o) => fn_options.merge(&o),
+                    Err(e) => diagnostic::error(e.span(), e.to_string()),
                 }
             }
-            let pat: syn::Pat = Box::leak(tp.pat.clone()).clone();
-            let ty: syn::Type = Box::leak(tp.ty.clone()).clone();
-            input_types.push(ty);
-            param_assign.push(pat);
-            default_values.push(assign);
         }
-    }
-    let body = ast.block.stmts;
-    let not_defined = NAMINGS.lock().unwrap().insert(ast.sig.ident.to_string());
-    let prepare = if not_defined {
-        quote!(
-            #[doc(hidden)]
-            #[allow(non_camel_case_types)]
-            #[allow(dead_code)]
-            #[derive(Copy, Clone)]
-            #vis struct #shared_type;
-            unsafe impl Send for #shared_type {}
-            unsafe impl Sync for #shared_type {}
-            #[allow(non_upper_case_globals)]
-            #vis static #ident: #shared_type = #shared_type;
-        )
-    } else {
-        quote!()
-    };
-    let mut results = vec![];
-    if constness.is_some() {
-        span.warning(
-            "const fn is not supported. ".to_owned()
-                + "Will ignore const to produce workable functions",
-        )
-        .emit();
-    }
-    let param_iter = param_variants(input_types, param_assign, default_values);
-    for (input_types, param_assign, defaults) in param_iter {
-        let input_types = quotation_expand!(input_types);
-        let param_assign = quotation_expand!(param_assign);
-        let block;
-        if unsafety.is_some() {
-            span.warning(
-                "unsafe fn is not supported. ".to_owned()
-                    + "Will wrap in a unsafe block to make function safe.",
-            )
-            .emit();
-            if asyncness.is_some() {
-                output = quote!(core::pin::Pin<Box<dyn core::future::Future<Output = #output>>>);
-                block = quote!(
-                    let #param_assign = args;
-                    #(#defaults)*
-                    unsafe {
-                        Box::pin(async move {
-                            #(#body)*
-                        })
-                    }
-                );
-            } else {
-                block = quote!(
-                    let #param_assign = args;
-                    #(#defaults)*
-                    unsafe {
-                        #(#body)*
-                    }
-                );
+        item_fn.attrs.retain(|attr| !is_overload_attr(attr));
+        let position = sets
+            .iter()
+            .position(|(ident, _)| ident == &item_fn.sig.ident);
+        match position {
+            Some(position) => sets[position].1.push((item_fn, fn_options, marked)),
+            None => {
+                sets.push((
+                    item_fn.sig.ident.clone(),
+                    vec![(item_fn, fn_options, marked)],
+                ));
+                // placeholder for the set, filled in below
+                items.push(None);
             }
-        } else if asyncness.is_some() {
-            output = quote!(core::pin::Pin<Box<dyn core::future::Future<Output = #output>>>);
-            block = quote!(
-                let #param_assign = args;
-                #(#defaults)*
-                Box::pin(async move {
-                    #(#body)*
-                })
-            );
-        } else {
-            block = quote!(
-                let #param_assign = args;
-                #(#defaults)*
-                #(#body)*
-            );
         }
-        let result = fn_impl!(
-            impl_generics,
-            input_types,
-            shared_type,
-            where_clause,
-            output,
-            attrs,
-            block
-        );
-        results.push(result);
     }
-    quote!(
-        #prepare
-        #(#results)*
-    )
-    .into()
+    let mut sets = sets.into_iter();
+    let items = items.into_iter().map(|i| match i {
+        Some(i) => quote!(#i),
+        None => {
+            let (ident, overloads) = sets.next().unwrap();
+            let sig = &overloads[0].0.sig;
+            if overloads.len() == 1
+                && !overloads[0].2
+                && !has_default_param(sig)
+                && !has_variadic_param(sig)
+            {
+                let item_fn = &overloads[0].0;
+                return quote!(#item_fn);
+            }
+            let overloads: Vec<_> = overloads
+                .into_iter()
+                .map(|(item_fn, options, _)| (item_fn, options))
+                .collect();
+            grouped_set(&ident, &overloads)
+        }
+    });
+    let attrs = &item.attrs;
+    let vis = &item.vis;
+    let unsafety = &item.unsafety;
+    let mod_token = &item.mod_token;
+    let ident = &item.ident;
+    let mut result = quote!(#(#attrs)* #vis #unsafety #mod_token #ident);
+    brace.surround(&mut result, |tokens| tokens.extend(items));
+    result.into()
 }
 
 #[proc_macro_attribute]
 pub fn overload(attr: TokenStream, item: TokenStream) -> TokenStream {
-    //let args = syn::parse_macro_input!(attr as syn::Attribute);
-    // not used, currently specialization on Trait parameter is not well defined
-    let _default = if let Ok(parsed_args) = syn::Attribute::parse_outer.parse(attr) {
-        if !parsed_args.is_empty()
-            && parsed_args[0].clone().into_token_stream().to_string() == "default"
+    let options = syn::parse_macro_input!(attr as options::Options);
+    if let Some(named) = &options.named {
+        if parse::<syn::ItemFn>(item.clone()).is_err() {
+            diagnostic::error(
+                named.span(),
+                "`named` names the arguments of a single function overload",
+            );
+        }
+    }
+    for (set, option) in [(options.returns, "returns"), (options.dynamic, "dynamic")] {
+        if set
+            && parse::<syn::ItemFn>(item.clone()).is_err()
+            && parse::<syn::ItemMod>(item.clone()).is_err()
         {
-            quote!(default)
+            diagnostic::error(
+                proc_macro2::Span::call_site(),
+                format!("`{}` is only supported on free functions", option),
+            );
+        }
+    }
+    let expanded: proc_macro2::TokenStream = backend::with(options.stable, || {
+        if let Ok(ast) = parse::<syn::ItemTrait>(item.clone()) {
+            process_trait(ast, &options).into()
+        } else if let Ok(ast) = parse::<syn::ItemImpl>(item.clone()) {
+            process_impl(ast, &options).into()
+        } else if let Ok(ast) = parse::<syn::ItemFn>(item.clone()) {
+            process_fn(ast, &options).into()
+        } else if let Ok(ast) = parse::<syn::ItemMod>(item.clone()) {
+            process_mod(ast, &options).into()
         } else {
+            for tree in item.clone().into_iter() {
+                diagnostic::warning(
+                    tree.span().into(),
+                    "overload is only applicable to trait, impl, module and function",
+                );
+            }
             quote!()
         }
-    } else {
-        quote!()
-    };
-    if let Ok(ast) = parse::<syn::ItemTrait>(item.clone()) {
-        return process_trait(ast);
-    } else if let Ok(ast) = parse::<syn::ItemImpl>(item.clone()) {
-        return process_impl(ast);
-    } else if let Ok(ast) = parse::<syn::ItemFn>(item.clone()) {
-        return process_fn(ast);
-    } else {
-        for tree in item.into_iter() {
-            tree.span()
-                .warning("overload is only applicable to trait, impl, and function")
-                .emit();
-        }
-    }
-    quote!().into()
+    });
+    let errors = diagnostic::take_errors();
+    quote!(
+        #expanded
+        #errors
+    )
+    .into()
+}
+
+#[proc_macro]
+pub fn overload_set(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as OverloadSet);
+    let expanded: proc_macro2::TokenStream = process_set(input).into();
+    let errors = diagnostic::take_errors();
+    quote!(
+        #expanded
+        #errors
+    )
+    .into()
+}
+
+// the second half of `#[overload] impl Trait for T`, invoked by the macro of the trait
+#[doc(hidden)]
+#[proc_macro]
+pub fn __overload_impl(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as TraitImpl);
+    let expanded: proc_macro2::TokenStream = process_trait_impl(input).into();
+    let errors = diagnostic::take_errors();
+    quote!(
+        #expanded
+        #errors
+    )
+    .into()
 }
@@ -0,0 +1,130 @@
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream, Result};
+use syn::punctuated::Punctuated;
+
+// flags accepted in `#[overload(...)]`, and `named = Ident`, `dynamic = Ident` and
+// `macro = crate::path`
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    // `macro`: also generate a `macro_rules!` call macro named after the overload set
+    pub call_macro: bool,
+    // `macro = crate::geometry`: the module of the set, from the crate root, through which the
+    // call macro reaches it. the crate root if left out
+    pub macro_path: Option<syn::Path>,
+    // `send`: async overloads return `Send` futures
+    pub send: bool,
+    // `unboxed`: async overloads return named futures (`type_alias_impl_trait`) instead of
+    // boxing them. nightly only, the stable backend keeps boxing
+    pub unboxed: bool,
+    // `named = XddArgs`: the parameters of a function overload as a struct, which the set takes
+    // in place of positional arguments
+    pub named: Option<syn::Ident>,
+    // `returns`: free function overloads may differ in their return type only, the type the
+    // caller expects picks one. they implement `overloadf::ReturnOverload` in place of the Fn*
+    // traits
+    pub returns: bool,
+    // `dynamic`: free function overloads taking trait objects pick the most specific overload of
+    // the set by the runtime types of the arguments (see `dispatch_entry`)
+    pub dynamic: bool,
+    // `dynamic = Shape`: the trait whose objects the `Dispatch` entry point of the set takes,
+    // for sets whose first overload takes none
+    pub dispatches: Option<syn::Ident>,
+    // `stable`: generate code that builds on the stable toolchain. the set implements a sealed
+    // dispatch trait for its argument tuples in place of the Fn* traits, and is called through
+    // its `call` method
+    pub stable: bool,
+}
+
+impl Options {
+    // adds the flags set in `other`
+    pub fn merge(&mut self, other: &Options) {
+        self.call_macro |= other.call_macro;
+        self.send |= other.send;
+        self.unboxed |= other.unboxed;
+        self.returns |= other.returns;
+        self.dynamic |= other.dynamic;
+        self.stable |= other.stable;
+        if other.macro_path.is_some() {
+            self.macro_path = other.macro_path.clone();
+        }
+        if other.named.is_some() {
+            self.named = other.named.clone();
+        }
+        if other.dispatches.is_some() {
+            self.dispatches = other.dispatches.clone();
+        }
+    }
+}
+
+impl Parse for Options {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut options = Options::default();
+        let flags = Punctuated::<Flag, Token![,]>::parse_terminated(input)?;
+        for Flag(flag, value) in flags {
+            if let ("macro", Some(path)) = (flag.to_string().as_str(), &value) {
+                if path.leading_colon.is_some()
+                    || path
+                        .segments
+                        .first()
+                        .is_none_or(|segment| segment.ident != "crate")
+                {
+                    return Err(syn::Error::new_spanned(
+                        path,
+                        "`macro` takes the module of the set from the crate root: \
+                         `macro = crate::geometry`",
+                    ));
+                }
+                options.call_macro = true;
+                options.macro_path = Some(path.clone());
+                continue;
+            }
+            let value = match value.map(|path| path.require_ident().cloned()) {
+                Some(Ok(value)) => Some(value),
+                Some(Err(e)) => return Err(e),
+                None => None,
+            };
+            match (flag.to_string().as_str(), value) {
+                ("named", Some(value)) => options.named = Some(value),
+                ("macro", None) => options.call_macro = true,
+                ("send", None) => options.send = true,
+                ("unboxed", None) => options.unboxed = true,
+                ("returns", None) => options.returns = true,
+                ("dynamic", None) => options.dynamic = true,
+                ("stable", None) => options.stable = true,
+                ("dynamic", Some(value)) => {
+                    options.dynamic = true;
+                    options.dispatches = Some(value);
+                }
+                ("named", None) => {
+                    return Err(syn::Error::new(
+                        flag.span(),
+                        "`named` takes the name of the argument struct: `named = XddArgs`",
+                    ))
+                }
+                _ => {
+                    return Err(syn::Error::new(
+                        flag.span(),
+                        format!("unknown overload option `{}`", flag),
+                    ))
+                }
+            }
+        }
+        Ok(options)
+    }
+}
+
+// `flag`, `flag = Ident` or `flag = crate::path`
+struct Flag(syn::Ident, Option<syn::Path>);
+
+impl Parse for Flag {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let flag = input.call(syn::Ident::parse_any)?;
+        let value = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            Some(input.call(syn::Path::parse_mod_style)?)
+        } else {
+            None
+        };
+        Ok(Self(flag, value))
+    }
+}
@@ -0,0 +1,342 @@
+// detection of overloads that the Fn* impls can't tell apart.
+//
+// this is a syntactic approximation: rustc decides the real overlap of the generated impls, with
+// the trait bounds of the types involved at hand. what is reported here are the obvious cases,
+// which rustc only reports as "conflicting implementations": an overload repeated, a generic
+// parameter against any type it certainly accepts (an unbounded one, one whose bounds another
+// parameter has too, or a primitive type implementing all of its bounds), and the shorter
+// arities that default parameters add colliding with another overload.
+use crate::fn_struct;
+use crate::input_iter::InputIter;
+use quote::ToTokens;
+use std::collections::HashMap;
+
+// traits that `&T` implements whenever `T` does
+const REFERENCE_FORWARDED: &[&str] = &[
+    "Debug",
+    "Display",
+    "Hash",
+    "PartialEq",
+    "Eq",
+    "PartialOrd",
+    "Ord",
+    "AsRef",
+    "Clone",
+    "Copy",
+];
+
+// traits of the standard library that every primitive number implements. the operators (and
+// `PartialEq` and `PartialOrd`) only with the number itself on the right hand side
+const NUMBER_TRAITS: &[&str] = &[
+    "Copy",
+    "Clone",
+    "Debug",
+    "Display",
+    "Default",
+    "PartialEq",
+    "PartialOrd",
+    "Send",
+    "Sync",
+    "Unpin",
+    "FromStr",
+    "Sum",
+    "Product",
+    "Add",
+    "Sub",
+    "Mul",
+    "Div",
+    "Rem",
+    "AddAssign",
+    "SubAssign",
+    "MulAssign",
+    "DivAssign",
+    "RemAssign",
+];
+
+// the ones integers implement on top of `NUMBER_TRAITS`
+const INTEGER_TRAITS: &[&str] = &[
+    "Eq",
+    "Ord",
+    "Hash",
+    "BitAnd",
+    "BitOr",
+    "BitXor",
+    "Not",
+    "BitAndAssign",
+    "BitOrAssign",
+    "BitXorAssign",
+];
+
+// the ones `bool` and `char` implement
+const BOOL_TRAITS: &[&str] = &[
+    "Copy",
+    "Clone",
+    "Debug",
+    "Display",
+    "Default",
+    "PartialEq",
+    "Eq",
+    "PartialOrd",
+    "Ord",
+    "Hash",
+    "Send",
+    "Sync",
+    "Unpin",
+    "FromStr",
+    "BitAnd",
+    "BitOr",
+    "BitXor",
+    "Not",
+    "BitAndAssign",
+    "BitOrAssign",
+    "BitXorAssign",
+];
+const CHAR_TRAITS: &[&str] = &[
+    "Copy",
+    "Clone",
+    "Debug",
+    "Display",
+    "Default",
+    "PartialEq",
+    "Eq",
+    "PartialOrd",
+    "Ord",
+    "Hash",
+    "Send",
+    "Sync",
+    "Unpin",
+    "FromStr",
+];
+
+// the traits of the standard library that the primitive type `ty` certainly implements
+fn primitive_traits(ty: &str) -> &'static [&'static [&'static str]] {
+    match ty {
+        "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64" | "u128"
+        | "usize" => &[NUMBER_TRAITS, INTEGER_TRAITS],
+        "f32" | "f64" => &[NUMBER_TRAITS],
+        "bool" => &[BOOL_TRAITS],
+        "char" => &[CHAR_TRAITS],
+        _ => &[],
+    }
+}
+
+// a trait bound of a generic parameter: the name of the trait, and its generic arguments
+struct Bound {
+    name: String,
+    arguments: Vec<String>,
+}
+
+impl PartialEq for Bound {
+    fn eq(&self, other: &Bound) -> bool {
+        self.name == other.name && self.arguments == other.arguments
+    }
+}
+
+impl Bound {
+    // whether the primitive type `ty` certainly implements this bound of the parameter `param`.
+    // generic arguments, like the right hand side of `Mul<i32>` or its `Output = T`, have to be
+    // `ty` itself, or the parameter standing for it
+    fn implemented_by(&self, ty: &str, param: &str) -> bool {
+        primitive_traits(ty)
+            .iter()
+            .any(|traits| traits.contains(&self.name.as_str()))
+            && self.arguments.iter().all(|argument| {
+                let argument = argument.strip_prefix("Output = ").unwrap_or(argument);
+                argument == ty || argument == param
+            })
+    }
+}
+
+pub struct Overload {
+    // argument types of every arity the overload can be called with, the full one first. with
+    // `returning`, each followed by the return type
+    variants: Vec<Vec<syn::Type>>,
+    returning: bool,
+    // generic type parameters with the traits bounding them
+    generics: HashMap<String, Vec<Bound>>,
+}
+
+pub struct Overlap {
+    // argument types taken by both overloads, preferring the concrete ones
+    pub arguments: String,
+    // the overlap involves an arity added by default parameters
+    pub defaults: bool,
+    // the return type both overloads have, when they are told apart by it
+    pub output: Option<String>,
+}
+
+fn trait_bounds<'a, I: IntoIterator<Item = &'a syn::TypeParamBound>>(bounds: I) -> Vec<Bound> {
+    bounds
+        .into_iter()
+        .filter_map(|bound| match bound {
+            syn::TypeParamBound::Trait(t)
+                if matches!(t.modifier, syn::TraitBoundModifier::None) =>
+            {
+                t.path.segments.last()
+            }
+            _ => None,
+        })
+        .map(|segment| Bound {
+            name: segment.ident.to_string(),
+            arguments: match &segment.arguments {
+                syn::PathArguments::AngleBracketed(a) => a
+                    .args
+                    .iter()
+                    .map(|argument| argument.to_token_stream().to_string())
+                    .collect(),
+                syn::PathArguments::Parenthesized(p) => vec![p.to_token_stream().to_string()],
+                syn::PathArguments::None => vec![],
+            },
+        })
+        .filter(|bound| bound.name != "Sized")
+        .collect()
+}
+
+// the generic type parameters of `sig`, with their bounds from both the parameter list and the
+// where clause
+fn generic_bounds(sig: &syn::Signature) -> HashMap<String, Vec<Bound>> {
+    let mut generics: HashMap<String, Vec<Bound>> = HashMap::new();
+    for param in sig.generics.type_params() {
+        generics.insert(param.ident.to_string(), trait_bounds(&param.bounds));
+    }
+    for predicate in sig.generics.where_clause.iter().flat_map(|w| &w.predicates) {
+        if let syn::WherePredicate::Type(predicate) = predicate {
+            let bounded = predicate.bounded_ty.to_token_stream().to_string();
+            if let Some(bounds) = generics.get_mut(&bounded) {
+                bounds.extend(trait_bounds(&predicate.bounds));
+            }
+        }
+    }
+    generics
+}
+
+impl Overload {
+    pub fn new(sig: &syn::Signature) -> Self {
+        let generics = generic_bounds(sig);
+        let mut types = vec![];
+        let mut pats = vec![];
+        let mut defaults = vec![];
+        for input in &sig.inputs {
+            match input {
+                syn::FnArg::Receiver(r) => {
+                    types.push((*r.ty).clone());
+                    defaults.push(None);
+                }
+                syn::FnArg::Typed(t) => {
+                    types.push((*t.ty).clone());
+                    defaults.push(fn_struct::default_value(&t.attrs));
+                }
+            }
+            pats.push(syn::parse_quote!(_));
+        }
+        let variadic = match sig.inputs.last() {
+            Some(syn::FnArg::Typed(t)) => fn_struct::variadic_arity(&t.attrs).and_then(Result::ok),
+            _ => None,
+        };
+        let variants = InputIter::new(types, pats, defaults, variadic)
+            .map(|variant| variant.inputs)
+            .collect();
+        Self {
+            variants,
+            generics,
+            returning: false,
+        }
+    }
+
+    // the single arity of `sig` that takes `types`, to tell apart the arities its default and
+    // variadic parameters add
+    pub fn arity(sig: &syn::Signature, types: &[syn::Type]) -> Self {
+        Self {
+            variants: vec![types.to_vec()],
+            generics: generic_bounds(sig),
+            returning: false,
+        }
+    }
+
+    // the overload as told apart by its return type too, see `#[overload(returns)]`
+    pub fn returning(mut self, sig: &syn::Signature) -> Self {
+        let output: syn::Type = match &sig.output {
+            syn::ReturnType::Default => syn::parse_quote!(()),
+            syn::ReturnType::Type(_, ty) => (**ty).clone(),
+        };
+        for variant in &mut self.variants {
+            variant.push(output.clone());
+        }
+        self.returning = true;
+        self
+    }
+
+    // the generic parameter `ty` is, with its bounds
+    fn param(&self, ty: &syn::Type) -> Option<&Vec<Bound>> {
+        match ty {
+            syn::Type::Path(p) if p.qself.is_none() => {
+                self.generics.get(&p.path.get_ident()?.to_string())
+            }
+            _ => None,
+        }
+    }
+
+    // whether every argument `other` takes at `b` is certainly taken by `self` at `a`
+    fn accepts(&self, a: &syn::Type, other: &Overload, b: &syn::Type) -> bool {
+        let Some(bounds) = self.param(a) else {
+            return false;
+        };
+        if bounds.is_empty() {
+            return true;
+        }
+        if let Some(other_bounds) = other.param(b) {
+            return bounds.iter().all(|t| other_bounds.contains(t));
+        }
+        match b {
+            syn::Type::Reference(r) if r.mutability.is_none() => {
+                other.param(&r.elem).is_some_and(|other_bounds| {
+                    bounds.iter().all(|t| {
+                        REFERENCE_FORWARDED.contains(&t.name.as_str()) && other_bounds.contains(t)
+                    })
+                })
+            }
+            syn::Type::Path(p) if p.qself.is_none() => {
+                let ty = p.path.to_token_stream().to_string();
+                let param = a.to_token_stream().to_string();
+                bounds.iter().all(|t| t.implemented_by(&ty, &param))
+            }
+            _ => false,
+        }
+    }
+
+    fn unify(&self, a: &syn::Type, other: &Overload, b: &syn::Type) -> bool {
+        self.accepts(a, other, b)
+            || other.accepts(b, self, a)
+            || (self.param(a).is_none()
+                && other.param(b).is_none()
+                && a.to_token_stream().to_string() == b.to_token_stream().to_string())
+    }
+
+    pub fn overlap(&self, other: &Overload) -> Option<Overlap> {
+        for (i, a) in self.variants.iter().enumerate() {
+            for (j, b) in other.variants.iter().enumerate() {
+                if a.len() == b.len() && a.iter().zip(b).all(|(a, b)| self.unify(a, other, b)) {
+                    let mut arguments: Vec<_> = a
+                        .iter()
+                        .zip(b)
+                        .map(|(a, b)| match other.param(b) {
+                            Some(_) => a.to_token_stream().to_string(),
+                            None => b.to_token_stream().to_string(),
+                        })
+                        .collect();
+                    let output = if self.returning {
+                        arguments.pop()
+                    } else {
+                        None
+                    };
+                    return Some(Overlap {
+                        arguments: format!("({})", arguments.join(", ")),
+                        defaults: i > 0 || j > 0,
+                        output,
+                    });
+                }
+            }
+        }
+        None
+    }
+}
@@ -1,12 +1,10 @@
-#![feature(fn_traits, unboxed_closures, const_extern_fn)]
-
 //! # Overloadf
 //!
 //! ** Let function overloading possible in rust **
 //!
-//! With a single attribute on top of the function, you can overload the function with different
-//! parameters. Current implementation still has some flaws and todo items, so use at your own
-//! risk.
+//! With a single attribute on top of a module or an impl block, or with the `overload_set!`
+//! macro, you can overload functions with different parameters. Current implementation still has
+//! some flaws and todo items, so use at your own risk.
 //!
 //! This library is based on some unstable features.
 //! To use this library, please put the following lines in crate root and the beginning of test
@@ -15,12 +13,40 @@
 //! #![feature(fn_traits, unboxed_closures)]
 //! ```
 //!
+//! On the stable toolchain, use `#[overload(stable)]` instead. Such overload sets implement a
+//! sealed dispatch trait for their argument tuples in place of the `Fn*` traits, and are invoked
+//! through the generic `call` entry point with the arguments packed in a tuple. The option only
+//! changes the set it is given to, so crates using either form can be built together:
+//! ```rust
+//! use overloadf::*;
+//!
+//! overload_set! {
+//!     #[overload(stable)]
+//!     pub fn xdd(number: i32) -> i32 {
+//!         number * 3
+//!     }
+//!
+//!     #[overload(stable)]
+//!     pub fn xdd(number: i32, scale: u8) -> i32 {
+//!         number * scale as i32
+//!     }
+//! }
+//!
+//! assert_eq!(xdd.call((3_i32,)), 9_i32);
+//! assert_eq!(xdd.call((3_i32, 2_u8)), 6_i32);
+//! ```
+//!
 //! There are some features that cannot be achieved until now:
-//! - unsafe function overloading
-//! - const function overloading
 //! - different privacy setting on function overloading (will pickup the privacy setting in first
-//! function and apply to all)
-//! - function overloading inside traits (for limited cases)
+//!   function and apply to all)
+//! - function overloading inside traits (for limited cases). the overloads of an impl are matched
+//!   to those of the trait by their parameter types, which have to be written the way the trait
+//!   declares them, up to the parameters of the trait (`Self::J` can't be given as `i32`)
+//! - method call syntax with a varying number of arguments. the method call syntax of an
+//!   overloaded method takes its arguments like the overloads do only if they all take the same
+//!   number of them. otherwise the arguments after the receiver are passed as one value: `()`, a
+//!   single value or a tuple (`haha.normal(())`, `haha.normal("abc")`, `haha.normal((1, 2))`).
+//!   the path call syntax takes them one by one in either case (`Haha::normal(&haha, 1, 2)`)
 //!
 //! ## Examples:
 //! simple one:
@@ -29,31 +55,114 @@
 //!
 //! use overloadf::*;
 //!
-//! #[overload]
-//! pub fn xdd() -> i32 {
-//!     5_i32
+//! overload_set! {
+//!     pub fn xdd() -> i32 {
+//!         5_i32
+//!     }
+//!
+//!     pub fn xdd(number: i32) -> i32 {
+//!         number * 3
+//!     }
+//!
+//!     pub fn xdd(number: u8) {
+//!         println!("{}", number);
+//!     }
 //! }
 //!
+//! assert_eq!(xdd(3_i32), 9_i32);
+//! assert_eq!(xdd(), 5_i32);
+//! ```
+//!
+//! the overloads of a set are declared together, so that each expansion sees the whole set.
+//! `overload_set!` overloads the functions in it, and `#[overload]` on a module groups the
+//! functions of that module on the spot: every set of functions sharing a name, or having default
+//! parameters, is overloaded, and its generated items stay inside the module. options on a
+//! function inside the module add to the module's:
+//! ```rust
+//! #![feature(fn_traits, unboxed_closures)]
+//! use overloadf::*;
+//!
 //! #[overload]
-//! pub fn xdd(number: i32) -> i32 {
-//!     number * 3
+//! mod text {
+//!     pub fn parse(text: &str) -> usize {
+//!         text.len()
+//!     }
+//!     pub fn parse(bytes: &[u8]) -> usize {
+//!         bytes.len() * 2
+//!     }
+//!     pub fn untouched(number: i32) -> i32 {
+//!         number
+//!     }
 //! }
-//!
 //! #[overload]
-//! pub fn xdd(number: u8) {
-//!     println!("{}", number);
+//! mod number {
+//!     pub fn parse(number: i32) -> i32 {
+//!         number * 3
+//!     }
+//!     pub fn parse(number: u8) -> u8 {
+//!         number + 1
+//!     }
 //! }
+//! assert_eq!(text::parse("abc"), 3);
+//! assert_eq!(text::parse(&b"abc"[..]), 6);
+//! assert_eq!(text::untouched(4), 4);
+//! assert_eq!(number::parse(3_i32), 9);
+//! assert_eq!(number::parse(3_u8), 4);
+//! ```
 //!
-//! #[overload]
-//! pub unsafe fn xdd(number: &u64) -> u64 {
-//!     let n = number as *const u64;
-//!     *n * 4
+//! `#[overload]` on a single function is deprecated. its overloads are expanded one at a time and
+//! grouped by their file and name in between, so sets of the same name in different modules of
+//! one file collide, and an overload expanded again, as rust-analyzer does while editing, may
+//! be taken for a new one. `#[allow(deprecated)]` on such an overload silences the warning.
+//!
+//! unsafe functions form sets of their own. such a set doesn't implement the `Fn*` traits, only
+//! [`UnsafeOverload`], so its overloads can only be called from unsafe code:
+//! ```rust
+//! #![feature(fn_traits, unboxed_closures)]
+//! use overloadf::*;
+//!
+//! overload_set! {
+//!     pub unsafe fn read(pointer: *const u64) -> u64 {
+//!         *pointer * 4
+//!     }
+//!     pub unsafe fn read(pointer: *const u8) -> u8 {
+//!         *pointer + 1
+//!     }
 //! }
+//! let n = 6_u64;
+//! assert_eq!(unsafe { read.call_unchecked((&n as *const u64,)) }, 24_u64);
+//! let n = 6_u8;
+//! assert_eq!(unsafe { read.call_unchecked((&n as *const u8,)) }, 7_u8);
+//! ```
+//! calling them like safe functions doesn't compile:
+//! ```rust,compile_fail
+//! #![feature(fn_traits, unboxed_closures)]
+//! use overloadf::*;
 //!
-//! assert_eq!(xdd(3_i32), 9_i32);
-//! let c: &u64 = &6_u64;
-//! assert_eq!(xdd(c), 24_u64); // unsafe function is not supported.
-//! assert_eq!(xdd(), 5_i32);
+//! overload_set! {
+//!     pub unsafe fn read(pointer: *const u64) -> u64 {
+//!         *pointer * 4
+//!     }
+//!     pub unsafe fn read(pointer: *const u8) -> u8 {
+//!         *pointer + 1
+//!     }
+//! }
+//! let n = 6_u64;
+//! read(&n as *const u64);
+//! ```
+//! and neither does mixing safe and unsafe overloads in one set:
+//! ```rust,compile_fail
+//! #![feature(fn_traits, unboxed_closures)]
+//! use overloadf::*;
+//!
+//! overload_set! {
+//!     pub fn read(number: u64) -> u64 {
+//!         number
+//!     }
+//!     pub unsafe fn read(pointer: *const u64) -> u64 {
+//!         *pointer
+//!     }
+//! }
 //! ```
 //!
 //! with generic and custom type:
@@ -63,16 +172,6 @@
 //! use overloadf::*;
 //! use std::ops::MulAssign;
 //! use std::fmt::Debug;
-//! #[overload]
-//! pub fn xdd<T: Copy + Debug + MulAssign<i32>>(mut number: T) -> T
-//! where
-//!     T: PartialEq,
-//! {
-//!     println!("number {:?}", number);
-//!     number *= 3_i32;
-//!     number
-//! }
-//!
 //! struct ABC;
 //! impl Debug for ABC {
 //!     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -80,9 +179,19 @@
 //!     }
 //! }
 //!
-//! #[overload]
-//! pub fn xdd(number: ABC) -> () {
-//!     println!("number {:?}", number);
+//! overload_set! {
+//!     pub fn xdd<T: Copy + Debug + MulAssign<i32>>(mut number: T) -> T
+//!     where
+//!         T: PartialEq,
+//!     {
+//!         println!("number {:?}", number);
+//!         number *= 3_i32;
+//!         number
+//!     }
+//!
+//!     pub fn xdd(number: ABC) -> () {
+//!         println!("number {:?}", number);
+//!     }
 //! }
 //! let aa = 123;
 //! assert_eq!(xdd(aa), 369);
@@ -93,32 +202,160 @@
 //! #![feature(fn_traits, unboxed_closures)]
 //! use overloadf::*;
 //!
-//! #[overload]
-//! pub async fn xdd(number: i32) -> i32 {
-//!     number + 3
-//! }
-//! #[overload]
-//! pub async fn xdd(number: i64) -> i64 {
-//!     number + 4
+//! overload_set! {
+//!     pub async fn xdd(number: i32) -> i32 {
+//!         number + 3
+//!     }
+//!     pub async fn xdd(number: i64) -> i64 {
+//!         number + 4
+//!     }
 //! }
 //! assert_eq!(futures::executor::block_on(xdd(3_i32)), 6);
 //! assert_eq!(futures::executor::block_on(xdd(3_i64)), 7);
 //! ```
 //!
-//! type conflict might happen if generic overlaps with the definition of implemented types:
+//! async overloads return boxed futures that are not `Send` by default. mark them with
+//! `#[overload(send)]` to get `Pin<Box<dyn Future<Output = T> + Send>>` instead, so they can be
+//! spawned on multi-threaded executors:
+//! ```rust
+//! #![feature(fn_traits, unboxed_closures)]
+//! use overloadf::*;
+//!
+//! overload_set! {
+//!     #[overload(send)]
+//!     pub async fn xdd(number: i32) -> i32 {
+//!         number + 3
+//!     }
+//!     #[overload(send)]
+//!     pub async fn xdd(number: i64) -> i64 {
+//!         number + 4
+//!     }
+//! }
+//! fn spawn<F: std::future::Future + Send>(future: F) -> F::Output {
+//!     futures::executor::block_on(future)
+//! }
+//! assert_eq!(spawn(xdd(3_i32)), 6);
+//! assert_eq!(spawn(xdd(3_i64)), 7);
+//! ```
+//! a body that holds something non-`Send` across an `.await` is then rejected at the overload:
+//! ```rust,compile_fail
+//! #![feature(fn_traits, unboxed_closures)]
+//! use overloadf::*;
+//!
+//! overload_set! {
+//!     #[overload(send)]
+//!     pub async fn xdd(number: i32) -> i32 {
+//!         let shared = std::rc::Rc::new(number);
+//!         async {}.await;
+//!         *shared + 3
+//!     }
+//! }
+//! ```
+//!
+//! with `#[overload(unboxed)]` the future is not boxed at all: each async overload gets a named
+//! future type through `type_alias_impl_trait`, so calls cost the same as a plain `async fn` and
+//! need no `alloc`. it can be combined with `send`. such overloads have to be declared at module
+//! level, not inside a function body. `#[overload(stable)]` ignores the option and keeps boxing:
+//! ```rust
+//! #![feature(fn_traits, unboxed_closures, type_alias_impl_trait)]
+//! use overloadf::*;
+//! use std::fmt::Debug;
+//!
+//! overload_set! {
+//!     #[overload(unboxed)]
+//!     pub async fn xdd(number: i32) -> i32 {
+//!         number + 3
+//!     }
+//!     #[overload(unboxed, send)]
+//!     pub async fn xdd<T: Debug + Send>(list: Vec<T>) -> String {
+//!         format!("{:?}", list)
+//!     }
+//! }
+//! fn main() {
+//!     let future = xdd(3_i32);
+//!     // the future is stored inline, not behind a pointer
+//!     assert!(core::mem::size_of_val(&future) <= 8);
+//!     assert_eq!(futures::executor::block_on(future), 6);
+//!     assert_eq!(futures::executor::block_on(xdd(vec![1_u8, 2])), "[1, 2]");
+//! }
+//! ```
+//!
+//! const functions get const `Fn*` impls, so they can be called in const contexts. this needs
+//! the `const_trait_impl` feature, and isn't available with `#[overload(stable)]`, which
+//! ignores const:
+//! ```rust
+//! #![feature(fn_traits, unboxed_closures, const_trait_impl)]
+//! use overloadf::*;
+//!
+//! #[derive(Debug, PartialEq)]
+//! pub struct Fixed(i64);
+//!
+//! overload_set! {
+//!     pub const fn fixed(number: i32) -> Fixed {
+//!         Fixed((number as i64) << 16)
+//!     }
+//!     pub const fn fixed(number: i32, fraction: u16) -> Fixed {
+//!         Fixed(((number as i64) << 16) | fraction as i64)
+//!     }
+//!     pub fn fixed(number: f64) -> Fixed {
+//!         Fixed((number * 65536.0) as i64)
+//!     }
+//! }
+//!
+//! const THREE: Fixed = fixed(3_i32);
+//! static THREE_AND_A_BIT: Fixed = fixed(3_i32, 5_u16);
+//! assert_eq!(THREE, Fixed(3 << 16));
+//! assert_eq!(THREE_AND_A_BIT, Fixed((3 << 16) | 5));
+//! assert_eq!(fixed(1.0), Fixed(1 << 16));
+//! ```
+//!
+//! overloads that can't be told apart are reported at the overloads themselves: a repeated
+//! overload, an unbounded generic against any type, a generic against a type that certainly
+//! satisfies its bounds (a primitive type implementing all of them, or `&U` with `U: Debug` for
+//! `T: Debug`), and the shorter arities added by default parameters colliding with another
+//! overload. the later of the two overloads is left out, so the error isn't followed by
+//! conflicting implementations. whether a generic takes any other type is left to the compiler:
 //! ```rust,compile_fail
 //! #![feature(fn_traits, unboxed_closures)]
 //!
 //! use overloadf::*;
 //! use std::ops::Mul;
 //! use std::fmt::Debug;
-//! #[overload]
-//! pub fn xdd(number: i32) -> i32 {
-//!     number * 2
+//! overload_set! {
+//!     pub fn xdd(number: i32) -> i32 {
+//!         number * 2
+//!     }
+//!     // error: overloads of `xdd` overlap: both take `(i32)`
+//!     pub fn xdd<T: Copy + Debug + Mul<i32>>(number: T) -> T {
+//!         number * 3_i32
+//!     }
+//! }
+//! ```
+//! ```rust,compile_fail
+//! #![feature(fn_traits, unboxed_closures)]
+//! use overloadf::*;
+//! overload_set! {
+//!     fn xdd(number: i32, scale: u8 = 2) -> i32 {
+//!         number * scale as i32
+//!     }
+//!     // error: overloads of `xdd` overlap: both take `(i32)` once default parameters are left out
+//!     fn xdd(number: i32) -> i32 {
+//!         number
+//!     }
 //! }
+//! ```
+//! ```rust,compile_fail
+//! #![feature(fn_traits, unboxed_closures)]
+//! use overloadf::*;
 //! #[overload]
-//! pub fn xdd<T: Copy + Debug + Mul<i32>>(number: T) -> T {
-//!     number * 3_i32
+//! mod numbers {
+//!     pub fn xdd(number: i32) -> i32 {
+//!         number
+//!     }
+//!     // error: overloads of `xdd` overlap: both take `(i32)`
+//!     pub fn xdd<T>(value: T) -> i32 {
+//!         0
+//!     }
 //! }
 //! ```
 //!
@@ -176,15 +413,17 @@
 //!
 //! impl<T: 'static + ?Sized> Xdd for T {}
 //! let obj = (&3_i32 as &(dyn Xdd));
-//! // here we lost the syntax sugar of calling obj.abc(c) directly
 //! assert_eq!(Xdd::abc(obj, 3_i32), 3_i32);
 //! assert_eq!(Xdd::abc(obj, 3_u32), 3_u32);
+//! // method call syntax goes through the generated extension trait
+//! assert_eq!(obj.abc(3_i32), 3_i32);
 //! ```
 //!
 //! trait with generics:
 //! ```rust
 //! #![feature(fn_traits, unboxed_closures)]
 //! use overloadf::*;
+//! use std::fmt::Debug;
 //! #[overload]
 //! trait Xdd<T: Sized>: Sized {
 //!     type J: Into<i32>;
@@ -225,6 +464,97 @@
 //! assert_eq!(Haha::new(3_u32), "please use new(input1, input2) instead".to_string());
 //! ```
 //!
+//! the impl finds the overloads of the trait through a hidden macro named like the trait, so
+//! qualified paths, re-exports and `use .. as` aliases of the trait work, and so do other
+//! crates: a public trait exports it as a hidden `#[macro_export]` macro, on both backends.
+//! default bodies stay in the trait, where they are resolved:
+//! ```rust
+//! #![feature(fn_traits, unboxed_closures)]
+//! use overloadf::*;
+//! pub mod api {
+//!     use overloadf::*;
+//!     fn base() -> u32 {
+//!         40
+//!     }
+//!     #[overload]
+//!     pub trait Xdd: Sized {
+//!         fn value(&self) -> u32;
+//!         fn value(&self, extra: u32) -> u32 {
+//!             self.value(()) + extra + base()
+//!         }
+//!     }
+//! }
+//! use api::Xdd as Renamed;
+//! struct Haha;
+//! #[overload]
+//! impl crate::api::Xdd for Haha {
+//!     fn value(&self) -> u32 {
+//!         1
+//!     }
+//! }
+//! struct Hoho;
+//! #[overload]
+//! impl Renamed for Hoho {
+//!     fn value(&self) -> u32 {
+//!         2
+//!     }
+//!     fn value(&self, extra: u32) -> u32 {
+//!         extra
+//!     }
+//! }
+//! fn main() {
+//!     // the trait and the extension trait of `value`
+//!     use api::*;
+//!     assert_eq!(Haha::value(&Haha, 1_u32), 42);
+//!     assert_eq!(Hoho.value(()), 2);
+//!     assert_eq!(Hoho.value(1_u32), 1);
+//! }
+//! ```
+//!
+//! impls of overloaded traits keep their generics and where clauses, so generic and blanket
+//! impls work like for any other trait:
+//! ```rust
+//! #![feature(fn_traits, unboxed_closures)]
+//! use overloadf::*;
+//! use std::fmt::Display;
+//! #[overload]
+//! trait Show: Sized {
+//!     fn show(&self) -> String;
+//!     fn show(&self, width: usize) -> String;
+//! }
+//! struct Wrapper<T>(T);
+//! #[overload]
+//! impl<T: Display> Show for Wrapper<T> {
+//!     fn show(&self) -> String {
+//!         format!("[{}]", self.0)
+//!     }
+//!     fn show(&self, width: usize) -> String {
+//!         format!("[{:>width$}]", self.0, width = width)
+//!     }
+//! }
+//! #[overload]
+//! trait Repeat: Sized {
+//!     fn repeat(self) -> Vec<Self>;
+//!     fn repeat(self, times: usize) -> Vec<Self>;
+//! }
+//! #[overload]
+//! impl<T> Repeat for T
+//! where
+//!     T: Clone,
+//! {
+//!     fn repeat(self) -> Vec<Self> {
+//!         vec![self.clone(), self]
+//!     }
+//!     fn repeat(self, times: usize) -> Vec<Self> {
+//!         vec![self; times]
+//!     }
+//! }
+//! assert_eq!(Wrapper(3).show(()), "[3]");
+//! assert_eq!(Wrapper("a").show(3_usize), "[  a]");
+//! assert_eq!(<u8 as Repeat>::repeat(1_u8), vec![1, 1]);
+//! assert_eq!(<&str as Repeat>::repeat("a", 3_usize), vec!["a", "a", "a"]);
+//! ```
+//!
 //! non-trait impl:
 //! ```rust
 //! #![feature(fn_traits, unboxed_closures)]
@@ -248,14 +578,20 @@
 //!             b: 2,
 //!         }
 //!     }
-//!     // self in function parameter will be converted to associated type
-//!     // that means no more syntax sugar like haha.normal()
+//!     // overloaded methods are converted to associated consts, the method call
+//!     // syntax is provided by the extension trait `OverloaderExt_Haha_normal`
 //!     pub fn normal(&self) -> String {
 //!         format!("{:?}", self)
 //!     }
 //!     pub fn normal(&self, prefix: &str) -> String {
 //!         format!("{} {:?}", prefix, self)
 //!     }
+//!     pub fn scaled(&self, x: u32, y: u32) -> u32 {
+//!         self.a * x + self.a * y
+//!     }
+//!     pub fn scaled(&self, x: i32, y: i32) -> i32 {
+//!         self.b * x + self.b * y
+//!     }
 //!     // function without overloading is not influenced
 //!     pub fn display(&self) -> String {
 //!         format!("{:?}", self)
@@ -270,13 +606,102 @@
 //! assert_eq!(Haha::normal(&haha), "Haha { a: 9, b: 2 }");
 //! assert_eq!(Haha::normal(&haha, "abc"), "abc Haha { a: 9, b: 2 }");
 //! assert_eq!(haha.display(), "Haha { a: 9, b: 2 }");
+//! // every overload of `scaled` takes two arguments, which the method call takes as well
+//! assert_eq!(haha.scaled(1_u32, 2_u32), 27_u32);
+//! assert_eq!(haha.scaled(1_i32, 2_i32), 6_i32);
+//! // the overloads of `normal` take no or one argument, which is passed as `()` or the value
+//! assert_eq!(haha.normal(()), "Haha { a: 9, b: 2 }");
+//! assert_eq!(haha.normal("abc"), "abc Haha { a: 9, b: 2 }");
+//! ```
+//!
+//! the impl may be generic, or name its type by a path. the overloaders of a generic impl take
+//! its parameters, and their impls its bounds:
+//! ```rust
+//! #![feature(fn_traits, unboxed_closures)]
+//! use overloadf::*;
+//! #[derive(Debug, PartialEq)]
+//! pub struct Stack<T> {
+//!     items: Vec<T>,
+//! }
+//! #[overload]
+//! impl<T: Clone> Stack<T> {
+//!     pub fn new(item: T) -> Self {
+//!         Self { items: vec![item] }
+//!     }
+//!     pub fn new(item: T, n: usize) -> Self {
+//!         Self { items: vec![item; n] }
+//!     }
+//!     pub fn push(&mut self, item: T) {
+//!         self.items.push(item);
+//!     }
+//!     pub fn push(&mut self, items: &[T]) {
+//!         self.items.extend_from_slice(items);
+//!     }
+//! }
+//! pub mod units {
+//!     pub struct Meters<N>(pub N);
+//! }
+//! #[overload]
+//! impl units::Meters<f64> {
+//!     pub fn add(&self, other: f64) -> f64 {
+//!         self.0 + other
+//!     }
+//!     pub fn add(&self, other: &Self) -> f64 {
+//!         self.0 + other.0
+//!     }
+//! }
+//! let mut stack = Stack::new(1);
+//! stack.push(2);
+//! Stack::push(&mut stack, &[3, 4][..]);
+//! assert_eq!(stack.items, vec![1, 2, 3, 4]);
+//! assert_eq!(Stack::<&str>::new("a", 2), Stack { items: vec!["a", "a"] });
+//! let meters = units::Meters(1.5);
+//! assert_eq!(meters.add(1.0), 2.5);
+//! assert_eq!(meters.add(&units::Meters(2.0)), 3.5);
+//! ```
+//!
+//! methods of overloaded traits keep the method call syntax as well. Every receiver kind works,
+//! but method lookup stops at the first receiver type that has an implementation, so the
+//! overloads of one method should share the same receiver kind:
+//! ```rust
+//! #![feature(fn_traits, unboxed_closures)]
+//! use overloadf::*;
+//! #[overload]
+//! trait Counter: Sized {
+//!     fn add(&mut self, n: u32);
+//!     fn add(&mut self, n: u32, times: u32);
+//!     fn get(&self) -> u32;
+//!     fn get(&self, offset: u32) -> u32;
+//! }
+//! struct Count(u32);
+//! #[overload]
+//! impl Counter for Count {
+//!     fn add(&mut self, n: u32) {
+//!         self.0 += n;
+//!     }
+//!     fn add(&mut self, n: u32, times: u32) {
+//!         self.0 += n * times;
+//!     }
+//!     fn get(&self) -> u32 {
+//!         self.0
+//!     }
+//!     fn get(&self, offset: u32) -> u32 {
+//!         self.0 + offset
+//!     }
+//! }
+//! let mut count = Count(0);
+//! count.add(2_u32);
+//! count.add((3_u32, 2_u32));
+//! assert_eq!(count.get(()), 8);
+//! assert_eq!(count.get(1_u32), 9);
 //! ```
 //!
 //! default attribute:
 //!
-//! in functions(not yet for traits), now you could decorate parameters with default values.
+//! in functions, methods and traits, now you could decorate parameters with default values.
 //! the reason why we don't support `parameter = value` syntax directly is that in derived
-//! `TokenStream`, values inside will go through compiler first for syntax check.
+//! `TokenStream`, values inside will go through compiler first for syntax check. only
+//! `overload_set!` takes it (see below).
 //! The default value syntax will be treated as a compile error and forbid us from parsing
 //! and generating valid Tokens.
 //!
@@ -284,23 +709,934 @@
 //! ```rust
 //! #![feature(fn_traits, unboxed_closures)]
 //! use overloadf::*;
-//! #[overload]
-//! fn xdd(#[default(= 5_i32)] a: i32, #[default(= 32_u64)] b: u64) -> u64 { b - (a as u64) }
+//! overload_set! {
+//!     fn xdd(#[default(= 5_i32)] a: i32, #[default(= 32_u64)] b: u64) -> u64 { b - (a as u64) }
+//! }
 //! assert_eq!(xdd(3_i32), 29_u64);
 //! assert_eq!(xdd(4_i32, 4_u64), 0);
 //! assert_eq!(xdd(), 27_u64);
-//! // compile error: expect i32 found u64
-//! // assert_eq!(xdd(6_u64), 1_u64);
+//! assert_eq!(xdd(6_u64), 1_u64);
+//! // compile error: expect i32 found u16
+//! // assert_eq!(xdd(6_u16), 1_u64);
 //! ```
 //!
 //! ```rust
 //! #![feature(fn_traits, unboxed_closures)]
 //! use overloadf::*;
-//! #[overload]
-//! fn xdd(#[default(= 5_i32)] a: i32, b: u8, #[default(= 32_u64)] c: u64) -> u64 { c + (b as u64) - (a as u64) }
+//! overload_set! {
+//!     fn xdd(#[default(= 5_i32)] a: i32, b: u8, #[default(= 32_u64)] c: u64) -> u64 { c + (b as u64) - (a as u64) }
+//! }
 //! assert_eq!(xdd(4_i32, 7_u8), 35_u64);
 //! assert_eq!(xdd(3_u8), 30_u64);
 //! ```
+//!
+//! parameters are bound from left to right, and a left out parameter takes its default in its
+//! turn. a default can therefore use the parameters before it (`self` included), as well as the
+//! generics of the function, but not the parameters after it. a bare `#[default]` stands for
+//! `Default::default()`. any of the defaults may be left out, not only the trailing ones. a
+//! type parameter has to appear in some parameter without default, otherwise leaving out the
+//! defaults would leave it undetermined:
+//! ```rust
+//! #![feature(fn_traits, unboxed_closures)]
+//! use overloadf::*;
+//! overload_set! {
+//!     fn area(width: u32, #[default(= width)] height: u32, #[default] border: u8) -> u32 {
+//!         (width + border as u32) * (height + border as u32)
+//!     }
+//!     fn pad<T: Default + Clone>(mut items: Vec<T>, #[default(= items.len() + 1)] len: usize, #[default] value: Option<T>) -> Vec<T> {
+//!         items.resize(len, value.unwrap_or_default());
+//!         items
+//!     }
+//!     fn pad(text: &str) -> String {
+//!         format!(" {} ", text)
+//!     }
+//! }
+//! assert_eq!(area(3), 9);
+//! assert_eq!(area(3, 2_u32), 6);
+//! assert_eq!(area(3, 2, 1), 12);
+//! assert_eq!(area(3, 1_u8), 16);
+//! assert_eq!(pad(vec![1_u8]), vec![1, 0]);
+//! assert_eq!(pad(vec![1_u8], 3), vec![1, 0, 0]);
+//! assert_eq!(pad(vec!["a"], Some("b")), vec!["a", "b"]);
+//! assert_eq!(pad(vec!["a"], 3, Some("b")), vec!["a", "b", "b"]);
+//! ```
+//!
+//! leaving out different defaults must not give the same parameter types, as calls couldn't tell
+//! them apart. such defaults are reported as errors:
+//! ```compile_fail
+//! #![feature(fn_traits, unboxed_closures)]
+//! use overloadf::*;
+//! overload_set! {
+//!     fn area(width: u32, #[default(= width)] height: u32, #[default] border: u32) -> u32 {
+//!         (width + border) * (height + border)
+//!     }
+//! }
+//! ```
+//!
+//! default parameters work the same way in `impl` blocks and traits: a method with default
+//! parameters is a set of its own, even without other overloads. defaults of a trait method are
+//! declared in the trait and hold for every implementor. an impl may repeat them, but not change
+//! or add any:
+//! ```rust
+//! #![feature(fn_traits, unboxed_closures)]
+//! use overloadf::*;
+//! #[derive(Debug, PartialEq)]
+//! pub struct Conn {
+//!     host: String,
+//!     port: u16,
+//! }
+//! #[overload]
+//! impl Conn {
+//!     pub fn new(host: &str, #[default(= 80)] port: u16) -> Self {
+//!         Self { host: host.to_string(), port }
+//!     }
+//!     // defaults are evaluated in the body, and may use `self`
+//!     pub fn url(&self, #[default(= "http")] scheme: &str, #[default(= self.port)] port: u16) -> String {
+//!         format!("{}://{}:{}", scheme, self.host, port)
+//!     }
+//! }
+//! #[overload]
+//! pub trait Shape: Sized {
+//!     fn area(&self, #[default(= 1.0)] scale: f64) -> f64;
+//! }
+//! pub struct Square(f64);
+//! #[overload]
+//! impl Shape for Square {
+//!     fn area(&self, scale: f64) -> f64 {
+//!         self.0 * self.0 * scale
+//!     }
+//! }
+//! let conn = Conn::new("localhost");
+//! assert_eq!(conn, Conn::new("localhost", 80));
+//! assert_eq!(conn.url(()), "http://localhost:80");
+//! assert_eq!(conn.url(("ftp", 21)), "ftp://localhost:21");
+//! assert_eq!(Square(2.0).area(()), 4.0);
+//! assert_eq!(Square::area(&Square(2.0), 0.5), 2.0);
+//! ```
+//!
+//! positional calls can't tell apart defaults of the same type. `#[overload(named = XddArgs)]` on
+//! an overload also generates a struct of its parameters, which the set takes in place of them, so
+//! that any of the defaults can be left out. it is made by `new` from the parameters without
+//! default (or by `Default` when all have one), and has a setter for each parameter. names are
+//! checked at compile time, a misspelled one is a missing method:
+//! ```rust
+//! #![feature(fn_traits, unboxed_closures)]
+//! use overloadf::*;
+//! overload_set! {
+//!     #[overload(named = ConnectArgs)]
+//!     pub fn connect(host: &str, #[default(= 80)] port: u16, #[default(= false)] tls: bool) -> String {
+//!         format!("{}:{} {}", host, port, tls)
+//!     }
+//!     pub fn connect(port: u16) -> String {
+//!         format!("localhost:{}", port)
+//!     }
+//! }
+//! assert_eq!(connect(ConnectArgs::new("example.org").tls(true)), "example.org:80 true");
+//! assert_eq!(connect("example.org", 8080_u16), "example.org:8080 false");
+//! assert_eq!(connect(3000_u16), "localhost:3000");
+//! ```
+//! a parameter named `new` would clash with the constructor, and is rejected, as is one named
+//! `default` when all parameters have a default:
+//! ```rust,compile_fail
+//! #![feature(fn_traits, unboxed_closures)]
+//! use overloadf::*;
+//! overload_set! {
+//!     // error: `ItemArgs::new` makes the named arguments of `item`, rename the parameter `new`
+//!     #[overload(named = ItemArgs)]
+//!     pub fn item(name: &str, #[default(= false)] new: bool) -> String {
+//!         format!("{} {}", name, new)
+//!     }
+//!     pub fn item(id: u32) -> String {
+//!         id.to_string()
+//!     }
+//! }
+//! ```
+//!
+//! variadic attribute:
+//!
+//! `#[variadic(max = 12)]` on the last parameter of a function or method takes any number of
+//! arguments of its type, up to the maximum. `min` raises the fewest it takes, which a type
+//! parameter appearing only in it needs. the body sees the parameter as an array of the
+//! arguments, `[i64; 3]` when called with three of them. variadic parameters are not supported in
+//! traits, nor by `named`. every arity of a signature is generated on its own, one for each subset
+//! of its default parameters and number of variadic arguments, so a signature has at most 8
+//! default parameters, and at most 256 arities:
+//! ```rust
+//! #![feature(fn_traits, unboxed_closures)]
+//! use overloadf::*;
+//! overload_set! {
+//!     fn sum(#[variadic(max = 12)] xs: i64) -> i64 {
+//!         xs.iter().sum()
+//!     }
+//!     fn largest<T: PartialOrd + Copy>(#[variadic(min = 1, max = 6)] xs: T) -> T {
+//!         xs.iter().fold(xs[0], |m, &x| if x > m { x } else { m })
+//!     }
+//!     fn log(level: &str, #[variadic(max = 8)] parts: &dyn std::fmt::Display) -> String {
+//!         let parts: Vec<_> = parts.iter().map(|part| part.to_string()).collect();
+//!         format!("[{}] {}", level, parts.join(" "))
+//!     }
+//! }
+//! assert_eq!(sum(), 0);
+//! assert_eq!(sum(1), 1);
+//! assert_eq!(sum(1, 2, 3, 4), 10);
+//! assert_eq!(largest(3, 9, 2), 9);
+//! assert_eq!(largest("a"), "a");
+//! assert_eq!(log("info", &"took", &12, &"ms"), "[info] took 12 ms");
+//! ```
+//!
+//! return type overloading:
+//!
+//! rust picks an `Fn*` impl by the arguments alone, so overloads can't differ in their return type
+//! only, and a type parameter can't appear in the return type only. `#[overload(returns)]` on every
+//! overload of a free function set implements `ReturnOverload<Args, Output>` for them in place of
+//! the `Fn*` traits, which the type the caller expects selects. the set is called through
+//! `call_returning` with a tuple of the arguments, or through the `macro` option:
+//! ```rust
+//! use overloadf::*;
+//! use std::str::FromStr;
+//! overload_set! {
+//!     #[overload(returns)]
+//!     pub fn parse<T: FromStr>(s: &str) -> Option<T> {
+//!         s.parse().ok()
+//!     }
+//!     #[overload(returns, macro)]
+//!     pub fn parse(s: &str) -> bool {
+//!         s == "yes"
+//!     }
+//!     #[overload(returns)]
+//!     pub fn parse(s: &str, #[default(= 10)] radix: u32) -> u8 {
+//!         u8::from_str_radix(s, radix).unwrap()
+//!     }
+//! }
+//! let number: Option<i64> = parse.call_returning(("-3",));
+//! assert_eq!(number, Some(-3));
+//! let yes: bool = parse!("yes");
+//! assert!(yes);
+//! let byte: u8 = parse!("ff", 16);
+//! assert_eq!(byte, 255);
+//! ```
+//!
+//! reflection:
+//!
+//! every overload set implements `OverloadSet`, which lists its overloads with their parameter
+//! names, types, defaults and return type, as written in the source. the `Debug` output of a set is
+//! its name followed by these signatures:
+//! ```rust
+//! #![feature(fn_traits, unboxed_closures)]
+//! use overloadf::*;
+//! pub struct Conn;
+//! #[overload]
+//! impl Conn {
+//!     pub fn open(port: u16) -> Self {
+//!         Conn
+//!     }
+//!     pub fn open(host: &str, #[default(= 80)] port: u16) -> Self {
+//!         Conn
+//!     }
+//! }
+//! assert_eq!(Conn::open.name(), "Conn::open");
+//! let signatures: Vec<String> = Conn::open.overloads().map(|o| o.to_string()).collect();
+//! assert_eq!(
+//!     signatures,
+//!     ["fn open(port: u16) -> Self", "fn open(host: &str, port: u16 = 80) -> Self"]
+//! );
+//! ```
+//!
+//! dynamic calls:
+//!
+//! the sets of free functions and inherent methods also implement `DynOverload`, whose `call_dyn`
+//! takes the arguments as `Box<dyn Any>` and picks the overload by their number and types. it
+//! returns the boxed result, or an `OverloadError` listing the signatures of the candidates:
+//! ```rust
+//! #![feature(fn_traits, unboxed_closures)]
+//! use overloadf::*;
+//! use std::any::Any;
+//! #[derive(Debug, PartialEq)]
+//! pub struct Point(i32, i32);
+//! #[overload]
+//! impl Point {
+//!     pub fn new(x: i32, y: i32) -> Self {
+//!         Point(x, y)
+//!     }
+//!     pub fn new(xy: (i32, i32)) -> Self {
+//!         Point(xy.0, xy.1)
+//!     }
+//! }
+//! let args: Vec<Box<dyn Any>> = vec![Box::new((1, 2))];
+//! let point = Point::new.call_dyn(args).unwrap();
+//! assert_eq!(point.downcast_ref::<Point>(), Some(&Point(1, 2)));
+//! let error = Point::new.call_dyn(vec![Box::new("1, 2")]).unwrap_err();
+//! assert_eq!(error.candidates.len(), 2);
+//! ```
+//!
+//! dynamic dispatch:
+//!
+//! with `#[overload(dynamic)]` on every overload of a free function, a call passing trait objects
+//! goes to the most specific overload for the types behind them. overloads take shared references:
+//! `&dyn Trait` parameters are dispatched on, the others take a concrete type, which is checked
+//! through `Any`, so the trait needs `Any` as a supertrait. overloads with concrete types in
+//! earlier positions are the more specific ones, and the overload called with the trait objects
+//! runs itself when no other one takes the arguments. all overloads return the same type, and a set
+//! dispatches on one trait object type. [`Dispatch`] calls a set with a tuple of trait objects even
+//! when no overload takes them, returning an `OverloadError` when no overload takes the types
+//! behind them. if the first overload takes no trait objects, it names their trait:
+//! `#[overload(dynamic = Shape)]`:
+//! ```rust
+//! #![feature(fn_traits, unboxed_closures)]
+//! use overloadf::*;
+//! use std::any::Any;
+//! pub trait Shape: Any {}
+//! pub struct Circle;
+//! pub struct Square;
+//! impl Shape for Circle {}
+//! impl Shape for Square {}
+//! overload_set! {
+//!     #[overload(dynamic)]
+//!     pub fn collide(a: &dyn Shape, b: &dyn Shape) -> &'static str {
+//!         "shapes"
+//!     }
+//!     #[overload(dynamic)]
+//!     pub fn collide(a: &Circle, b: &dyn Shape) -> &'static str {
+//!         "circle and shape"
+//!     }
+//!     #[overload(dynamic)]
+//!     pub fn collide(a: &Circle, b: &Square) -> &'static str {
+//!         "circle and square"
+//!     }
+//! }
+//! let circle: Box<dyn Shape> = Box::new(Circle);
+//! let square: Box<dyn Shape> = Box::new(Square);
+//! assert_eq!(collide(&*circle, &*square), "circle and square");
+//! assert_eq!(collide(&*circle, &*circle), "circle and shape");
+//! assert_eq!(collide(&*square, &*circle), "shapes");
+//! assert_eq!(collide.dispatch((&*square, &*circle)), Ok("shapes"));
+//! ```
+//!
+//! documentation:
+//!
+//! rustdoc and rust-analyzer show an overload set as the value it is, documented with the
+//! signature of each overload, default values included, followed by the `///` docs of that overload.
+//! methods and the sets of `#[overload] mod` and `overload_set!` list all of their overloads there,
+//! so hovering over them in an editor shows every overload. the overloads of the deprecated
+//! `#[overload] fn` are expanded one at a time, so their value only lists the first overload, and
+//! says so, in rustdoc and on hover alike. the full list of such a set is left to rustdoc: the
+//! page of its overloader type (`Overloader_xdd` for `xdd`), which the value links to, has each
+//! overload on an empty `impl` block compiled for rustdoc only.
+//!
+//! overload_set:
+//!
+//! the function-like `overload_set!` is not syntax checked by the compiler before expansion, so
+//! it takes `parameter: Type = value` directly. every function in it is overloaded, grouped by
+//! name like in an `#[overload]` module, and options go in `#[overload(..)]` on the functions.
+//! seeing the whole set at once, it also reports overloads taking the same parameter types:
+//! ```rust
+//! #![feature(fn_traits, unboxed_closures)]
+//! use overloadf::*;
+//! overload_set! {
+//!     fn xdd(a: i32 = 5_i32, b: u64 = 32_u64) -> u64 {
+//!         b - (a as u64)
+//!     }
+//!     fn xdd(s: &str) -> usize {
+//!         s.len()
+//!     }
+//! }
+//! assert_eq!(xdd(), 27_u64);
+//! assert_eq!(xdd(3_i32), 29_u64);
+//! assert_eq!(xdd(4_i32, 4_u64), 0);
+//! assert_eq!(xdd("abc"), 3);
+//! ```
+//!
+//! ```rust,compile_fail
+//! #![feature(fn_traits, unboxed_closures)]
+//! use overloadf::*;
+//! overload_set! {
+//!     fn xdd(a: i32) -> i32 {
+//!         a
+//!     }
+//!     // error: duplicate overload of `xdd`
+//!     fn xdd(b: i32) -> u64 {
+//!         b as u64
+//!     }
+//! }
+//! ```
+//!
+//! call macro:
+//!
+//! `#[overload(macro)]` on one of the overloads also generates a `macro_rules!` macro named
+//! after the set. It packs its arguments into a tuple and dispatches them to the matching
+//! overload, which gives plain call syntax to `#[overload(stable)]` sets as well. The macro is
+//! re-exported next to the set, so importing the set also imports the macro, and a `pub` set
+//! exports it from its crate. Called by its bare name, the macro calls the set in scope where it
+//! is called:
+//! ```rust
+//! #![feature(fn_traits, unboxed_closures)]
+//! use overloadf::*;
+//! overload_set! {
+//!     #[overload(macro)]
+//!     fn xdd(#[default(= 5_i32)] a: i32, #[default(= 32_u64)] b: u64) -> u64 { b - (a as u64) }
+//!     fn xdd(s: &str) -> usize { s.len() }
+//! }
+//! assert_eq!(xdd!(), 27_u64);
+//! assert_eq!(xdd!(3_i32), 29_u64);
+//! assert_eq!(xdd!(4_i32, 4_u64,), 0);
+//! assert_eq!(xdd!("abc"), 3);
+//! ```
+//!
+//! `#[overload(macro = crate::geometry)]` names the module of the set from the crate root
+//! instead, which the macro reaches the set through, so that it works from any module, and from
+//! other crates for a `pub` set. When a single overload of the set takes as many arguments as
+//! the macro is given, and it's a `const fn`, the macro calls it directly, which works in const
+//! contexts on both backends. The sets of the deprecated `#[overload] fn` don't know their other
+//! overloads, and always call the set:
+//! ```rust
+//! use overloadf::*;
+//! pub mod geometry {
+//!     use overloadf::*;
+//!     overload_set! {
+//!         #[overload(stable, macro = crate::geometry)]
+//!         pub fn area(side: u32) -> u32 {
+//!             side * side
+//!         }
+//!         #[overload(stable)]
+//!         pub fn area(radius: f64) -> f64 {
+//!             3.0 * radius * radius
+//!         }
+//!         #[overload(stable)]
+//!         pub const fn area(width: u32, height: u32) -> u32 {
+//!             width * height
+//!         }
+//!     }
+//! }
+//! const AREA: u32 = geometry::area!(2, 3);
+//! fn main() {
+//!     assert_eq!(geometry::area!(3_u32), 9);
+//!     assert_eq!(geometry::area!(1.0), 3.0);
+//!     assert_eq!(AREA, 6);
+//! }
+//! ```
 
 pub extern crate overloadf_derive;
-pub use overloadf_derive::overload;
+#[doc(hidden)]
+pub use overloadf_derive::__overload_impl;
+pub use overloadf_derive::{overload, overload_set};
+
+/// Implemented by an overload set of `unsafe fn`s for the argument tuple of each overload, in
+/// place of the `Fn*` traits.
+pub trait UnsafeOverload<Args> {
+    type Output;
+    /// Calls the overload taking `Args`.
+    ///
+    /// # Safety
+    ///
+    /// The caller must uphold the safety contract of that overload.
+    unsafe fn call_unchecked(&self, args: Args) -> Self::Output;
+}
+
+/// Implemented by an overload set with `#[overload(returns)]` for the argument tuple and the
+/// return type of each overload, in place of the `Fn*` traits, so that the type the caller
+/// expects picks the overload.
+pub trait ReturnOverload<Args, Output> {
+    /// Calls the overload taking `Args` and returning `Output`.
+    fn call_returning(&self, args: Args) -> Output;
+}
+
+/// A parameter of an overload, as described by [`OverloadSet`]. Displayed the way
+/// `overload_set!` takes it, `a: i32 = 5`, and a receiver of type `Self`, `&Self` or `&mut Self`
+/// in its short form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParamInfo {
+    /// The pattern binding the parameter, `self` for the receiver.
+    pub name: &'static str,
+    /// The type of the parameter, or of each argument of a variadic one.
+    pub ty: &'static str,
+    /// The `#[default]` value, `Default::default()` for a bare `#[default]`.
+    pub default: Option<&'static str>,
+    /// The fewest and the most arguments a `#[variadic]` parameter takes.
+    pub variadic: Option<(usize, usize)>,
+}
+
+impl core::fmt::Display for ParamInfo {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match (self.name, self.ty, self.variadic) {
+            ("self", "Self", _) => f.write_str("self")?,
+            ("self", "&Self", _) => f.write_str("&self")?,
+            ("self", "&mut Self", _) => f.write_str("&mut self")?,
+            (name, ty, Some((min, max))) => write!(f, "{}: [{}; {}..={}]", name, ty, min, max)?,
+            (name, ty, None) => write!(f, "{}: {}", name, ty)?,
+        }
+        match self.default {
+            Some(default) => write!(f, " = {}", default),
+            None => Ok(()),
+        }
+    }
+}
+
+/// An overload of a set, as described by [`OverloadSet`]. Displayed as its signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OverloadInfo {
+    /// The name of the function or method.
+    pub name: &'static str,
+    /// The generic parameters with their bounds, `<T: Debug>`, empty if there are none.
+    pub generics: &'static str,
+    /// The parameters, the receiver of a method included.
+    pub params: &'static [ParamInfo],
+    /// The return type, `()` if there is none.
+    pub output: &'static str,
+    /// The where clause, `where T: Copy`, empty if there is none.
+    pub where_clause: &'static str,
+    pub is_async: bool,
+    pub is_unsafe: bool,
+}
+
+impl core::fmt::Display for OverloadInfo {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.is_async {
+            f.write_str("async ")?;
+        }
+        if self.is_unsafe {
+            f.write_str("unsafe ")?;
+        }
+        write!(f, "fn {}{}(", self.name, self.generics)?;
+        for (i, param) in self.params.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}", param)?;
+        }
+        f.write_str(")")?;
+        if self.output != "()" {
+            write!(f, " -> {}", self.output)?;
+        }
+        if !self.where_clause.is_empty() {
+            write!(f, " {}", self.where_clause)?;
+        }
+        Ok(())
+    }
+}
+
+/// Implemented by every overload set, describing its overloads. Its `Debug` prints them.
+///
+/// ```rust
+/// #![feature(fn_traits, unboxed_closures)]
+/// use overloadf::*;
+/// overload_set! {
+///     pub fn area(side: f64) -> f64 {
+///         side * side
+///     }
+///     pub fn area(width: u32, #[default(= 1)] height: u32) -> u32 {
+///         width * height
+///     }
+///     pub fn area<T: Into<f64>>(sides: Vec<T>) -> f64
+///     where
+///         T: Copy,
+///     {
+///         sides.iter().map(|side| (*side).into()).product()
+///     }
+/// }
+/// assert_eq!(area.name(), "area");
+/// assert_eq!(area.count(), 3);
+/// let second = area.overload(1).unwrap();
+/// assert_eq!(second.params[1].default, Some("1"));
+/// assert_eq!(second.to_string(), "fn area(width: u32, height: u32 = 1) -> u32");
+/// assert_eq!(
+///     area.overload(2).unwrap().to_string(),
+///     "fn area<T: Into<f64>>(sides: Vec<T>) -> f64 where T: Copy"
+/// );
+/// assert_eq!(
+///     format!("{:?}", area),
+///     "area [fn area(side: f64) -> f64, fn area(width: u32, height: u32 = 1) -> u32, \
+///      fn area<T: Into<f64>>(sides: Vec<T>) -> f64 where T: Copy]"
+/// );
+/// ```
+pub trait OverloadSet {
+    /// The name of the set, `xdd` for a function and `Type::method` for a method.
+    fn name(&self) -> &'static str;
+    /// The overload at `index`, in declaration order. Overloads that are repeated, and thus
+    /// rejected, are left out.
+    fn overload(&self, index: usize) -> Option<OverloadInfo>;
+    /// The number of overloads.
+    fn count(&self) -> usize {
+        (0..)
+            .take_while(|index| self.overload(*index).is_some())
+            .count()
+    }
+    /// The overloads, in declaration order.
+    fn overloads(&self) -> Overloads<'_, Self> {
+        Overloads {
+            set: self,
+            index: 0,
+        }
+    }
+}
+
+/// Iterator over the overloads of a set, see [`OverloadSet::overloads`].
+pub struct Overloads<'a, S: ?Sized> {
+    set: &'a S,
+    index: usize,
+}
+
+impl<S: OverloadSet + ?Sized> Iterator for Overloads<'_, S> {
+    type Item = OverloadInfo;
+    fn next(&mut self) -> Option<OverloadInfo> {
+        let overload = self.set.overload(self.index)?;
+        self.index += 1;
+        Some(overload)
+    }
+}
+
+// the `Debug` of an overload set: its name, followed by the signatures of its overloads
+#[doc(hidden)]
+pub fn __fmt_set<S: OverloadSet + ?Sized>(
+    set: &S,
+    f: &mut core::fmt::Formatter<'_>,
+) -> core::fmt::Result {
+    struct Signature(OverloadInfo);
+    impl core::fmt::Debug for Signature {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            core::fmt::Display::fmt(&self.0, f)
+        }
+    }
+    write!(f, "{} ", set.name())?;
+    f.debug_list()
+        .entries(set.overloads().map(Signature))
+        .finish()
+}
+
+/// The arguments of a dynamic call, see [`DynOverload`].
+pub type DynArgs = Vec<Box<dyn core::any::Any>>;
+
+/// Implemented by the overload sets of free functions and inherent methods, calling them with
+/// arguments whose types are only known at runtime.
+///
+/// An overload is called when it takes as many arguments as given, of the same types. Lifetimes
+/// in its parameter types are `'static` then, so a `&str` parameter takes a `&'static str`, and
+/// a `&self` receiver a `&'static Self`. Overloads with type parameters, `impl Trait` types, as
+/// well as `async` and `unsafe` ones can't be called this way. With `returns`, the first overload
+/// taking the arguments is called.
+///
+/// ```rust
+/// #![feature(fn_traits, unboxed_closures)]
+/// use overloadf::*;
+/// overload_set! {
+///     pub fn scale(value: i32, #[default(= 2)] by: i32) -> i32 {
+///         value * by
+///     }
+///     pub fn scale(text: &str) -> String {
+///         text.repeat(2)
+///     }
+/// }
+/// let result = scale.call_dyn(vec![Box::new(3_i32)]).unwrap();
+/// assert_eq!(result.downcast_ref::<i32>(), Some(&6));
+/// let result = scale.call_dyn(vec![Box::new("ab")]).unwrap();
+/// assert_eq!(result.downcast_ref::<String>().unwrap(), "abab");
+/// let error = scale.call_dyn(vec![Box::new(3_u8)]).unwrap_err();
+/// assert_eq!(
+///     error.to_string(),
+///     "no overload of `scale` takes the 1 argument given, the candidates are \
+///      `fn scale(value: i32, by: i32 = 2) -> i32` and `fn scale(text: &str) -> String`"
+/// );
+/// ```
+pub trait DynOverload: OverloadSet {
+    #[doc(hidden)]
+    fn __call_dyn(&self, args: &mut DynArgs) -> __DynResult;
+    /// Calls the overload taking `args`, returning its result.
+    fn call_dyn(&self, mut args: DynArgs) -> Result<Box<dyn core::any::Any>, OverloadError> {
+        self.__call_dyn(&mut args).ok_or_else(|| OverloadError {
+            set: self.name(),
+            arguments: args.iter().map(|arg| (**arg).type_id()).collect(),
+            candidates: self.overloads().collect(),
+        })
+    }
+}
+
+/// Implemented by the overload sets with `#[overload(dynamic)]`, calling the most specific
+/// overload for the types behind a tuple of trait objects, `(&dyn Trait, ..)`. Unlike a call of
+/// the set, this also works when no overload takes the trait objects themselves, and fails when no
+/// overload takes the types behind them.
+///
+/// ```rust
+/// #![feature(fn_traits, unboxed_closures)]
+/// use overloadf::*;
+/// use std::any::Any;
+/// pub trait Shape: Any {}
+/// pub struct Circle;
+/// pub struct Square;
+/// impl Shape for Circle {}
+/// impl Shape for Square {}
+/// overload_set! {
+///     #[overload(dynamic = Shape)]
+///     pub fn collide(a: &Circle, b: &Square) -> &'static str {
+///         "circle and square"
+///     }
+///     #[overload(dynamic)]
+///     pub fn collide(a: &Circle, b: &Circle) -> &'static str {
+///         "circles"
+///     }
+/// }
+/// let circle: Box<dyn Shape> = Box::new(Circle);
+/// let square: Box<dyn Shape> = Box::new(Square);
+/// assert_eq!(collide.dispatch((&*circle, &*square)), Ok("circle and square"));
+/// assert_eq!(collide.dispatch((&*circle, &*circle)), Ok("circles"));
+/// let error = collide.dispatch((&*square, &*circle)).unwrap_err();
+/// assert_eq!(error.arguments, [Any::type_id(&Square), Any::type_id(&Circle)]);
+/// ```
+pub trait Dispatch<Args>: OverloadSet {
+    /// The type the overloads return.
+    type Output;
+    /// Calls the most specific overload taking the types behind the trait objects in `args`,
+    /// returning its result.
+    fn dispatch(&self, args: Args) -> Result<Self::Output, OverloadError>;
+}
+
+/// The error of [`DynOverload::call_dyn`] and [`Dispatch::dispatch`], when no overload takes the
+/// arguments. Displayed with the signatures of the candidates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OverloadError {
+    /// The name of the set.
+    pub set: &'static str,
+    /// The types of the arguments given.
+    pub arguments: Vec<core::any::TypeId>,
+    /// The overloads of the set.
+    pub candidates: Vec<OverloadInfo>,
+}
+
+impl core::fmt::Display for OverloadError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "no overload of `{}` takes the {} argument{} given, the candidates are ",
+            self.set,
+            self.arguments.len(),
+            if self.arguments.len() == 1 { "" } else { "s" }
+        )?;
+        for (i, candidate) in self.candidates.iter().enumerate() {
+            if i > 0 {
+                f.write_str(if i + 1 == self.candidates.len() {
+                    " and "
+                } else {
+                    ", "
+                })?;
+            }
+            write!(f, "`{}`", candidate)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for OverloadError {}
+
+// a dynamic call checks the type of each argument, takes them as the parameter types of the
+// overload it found and boxes the result
+#[doc(hidden)]
+pub type __DynResult = Option<Box<dyn core::any::Any>>;
+
+#[doc(hidden)]
+pub fn __dyn_is<T: core::any::Any>(arg: &dyn core::any::Any) -> bool {
+    arg.is::<T>()
+}
+
+#[doc(hidden)]
+pub fn __dyn_take<T: core::any::Any>(args: &mut DynArgs) -> T {
+    *args.remove(0).downcast().unwrap()
+}
+
+#[doc(hidden)]
+pub fn __dyn_box<T: core::any::Any>(value: T) -> Box<dyn core::any::Any> {
+    Box::new(value)
+}
+
+// the overloads of an `#[overload] fn` set are expanded one at a time, so no expansion sees the
+// whole set. each overload implements `__OverloadEntry` for its position instead, and finds the
+// one after it through autoref specialization: called on `&&__Probe`, a method is that of
+// `__Found` if the entry exists, and that of `__Missing`, one dereference further, otherwise. the
+// set only probes its first position, so however many overloads it has, each of them adds a
+// single probe.
+#[doc(hidden)]
+pub trait __OverloadEntry<const N: usize> {
+    // the overload `n` positions after this one. positions of overloads left out for
+    // conflicting with another one don't count
+    fn __nth(&self, n: usize) -> Option<OverloadInfo>;
+    // calls the first overload from this one on that takes `args`
+    fn __call_dyn(&self, args: &mut DynArgs) -> __DynResult;
+}
+
+#[doc(hidden)]
+pub struct __Probe<'a, S, const N: usize>(pub &'a S);
+
+#[doc(hidden)]
+pub trait __Found {
+    fn __nth(&self, n: usize) -> Option<OverloadInfo>;
+    fn __call_dyn(&self, args: &mut DynArgs) -> __DynResult;
+}
+
+impl<S: __OverloadEntry<N>, const N: usize> __Found for &__Probe<'_, S, N> {
+    fn __nth(&self, n: usize) -> Option<OverloadInfo> {
+        __OverloadEntry::<N>::__nth(self.0, n)
+    }
+    fn __call_dyn(&self, args: &mut DynArgs) -> __DynResult {
+        __OverloadEntry::<N>::__call_dyn(self.0, args)
+    }
+}
+
+#[doc(hidden)]
+pub trait __Missing {
+    fn __nth(&self, n: usize) -> Option<OverloadInfo>;
+    fn __call_dyn(&self, args: &mut DynArgs) -> __DynResult;
+}
+
+impl<S, const N: usize> __Missing for __Probe<'_, S, N> {
+    fn __nth(&self, _n: usize) -> Option<OverloadInfo> {
+        None
+    }
+    fn __call_dyn(&self, _args: &mut DynArgs) -> __DynResult {
+        None
+    }
+}
+
+// overloads of a set with `dynamic` implement `__DispatchEntry` for their position, for the
+// trait object `D` they dispatch on (any, if they take none) and their output `R`. an overload
+// taking trait objects finds the first one like `__OverloadEntry`, and each the one after it,
+// ranks those taking the arguments and calls the most specific one.
+#[doc(hidden)]
+pub trait __DispatchEntry<const N: usize, D: ?Sized, R> {
+    // pushes the specificity of this overload and of the ones after it, by position: some if it
+    // takes `anys`, or the trait objects in `objects` where it takes a trait object. a parameter
+    // of a concrete type ranks higher than a trait object, the first parameters the highest.
+    fn __ranks(
+        &self,
+        anys: &[&(dyn core::any::Any + 'static)],
+        objects: &[Option<&D>],
+        ranks: &mut Vec<Option<u64>>,
+    );
+    // calls the overload at `index`, this one or one after it
+    fn __call(
+        &self,
+        index: usize,
+        anys: &[&(dyn core::any::Any + 'static)],
+        objects: &[Option<&D>],
+    ) -> Option<R>;
+}
+
+// the trait objects `Dispatch` takes, as a tuple of references
+#[doc(hidden)]
+pub trait __DispatchArgs<D: ?Sized> {
+    fn __objects(&self) -> Vec<&D>;
+}
+
+macro_rules! dispatch_args {
+    ($($object:ident)*) => {
+        impl<D: ?Sized> __DispatchArgs<D> for ($(dispatch_args!(@ref $object),)*) {
+            fn __objects(&self) -> Vec<&D> {
+                let ($($object,)*) = *self;
+                vec![$($object),*]
+            }
+        }
+    };
+    (@ref $object:ident) => { &D };
+}
+
+dispatch_args!();
+dispatch_args!(a);
+dispatch_args!(a b);
+dispatch_args!(a b c);
+dispatch_args!(a b c d);
+dispatch_args!(a b c d e);
+dispatch_args!(a b c d e f);
+dispatch_args!(a b c d e f g);
+dispatch_args!(a b c d e f g h);
+dispatch_args!(a b c d e f g h i);
+dispatch_args!(a b c d e f g h i j);
+dispatch_args!(a b c d e f g h i j k);
+dispatch_args!(a b c d e f g h i j k l);
+
+#[doc(hidden)]
+pub struct __DispatchProbe<'a, S, D: ?Sized, R, const N: usize>(
+    pub &'a S,
+    pub core::marker::PhantomData<fn(&D) -> R>,
+);
+
+#[doc(hidden)]
+pub trait __DispatchFound<D: ?Sized, R> {
+    fn __ranks(
+        &self,
+        anys: &[&(dyn core::any::Any + 'static)],
+        objects: &[Option<&D>],
+        ranks: &mut Vec<Option<u64>>,
+    );
+    fn __call(
+        &self,
+        index: usize,
+        anys: &[&(dyn core::any::Any + 'static)],
+        objects: &[Option<&D>],
+    ) -> Option<R>;
+}
+
+impl<S: __DispatchEntry<N, D, R>, D: ?Sized, R, const N: usize> __DispatchFound<D, R>
+    for &__DispatchProbe<'_, S, D, R, N>
+{
+    fn __ranks(
+        &self,
+        anys: &[&(dyn core::any::Any + 'static)],
+        objects: &[Option<&D>],
+        ranks: &mut Vec<Option<u64>>,
+    ) {
+        __DispatchEntry::<N, D, R>::__ranks(self.0, anys, objects, ranks)
+    }
+    fn __call(
+        &self,
+        index: usize,
+        anys: &[&(dyn core::any::Any + 'static)],
+        objects: &[Option<&D>],
+    ) -> Option<R> {
+        __DispatchEntry::<N, D, R>::__call(self.0, index, anys, objects)
+    }
+}
+
+#[doc(hidden)]
+pub trait __DispatchMissing<D: ?Sized, R> {
+    fn __ranks(
+        &self,
+        anys: &[&(dyn core::any::Any + 'static)],
+        objects: &[Option<&D>],
+        ranks: &mut Vec<Option<u64>>,
+    );
+    fn __call(
+        &self,
+        index: usize,
+        anys: &[&(dyn core::any::Any + 'static)],
+        objects: &[Option<&D>],
+    ) -> Option<R>;
+}
+
+impl<S, D: ?Sized, R, const N: usize> __DispatchMissing<D, R> for __DispatchProbe<'_, S, D, R, N> {
+    fn __ranks(
+        &self,
+        _anys: &[&(dyn core::any::Any + 'static)],
+        _objects: &[Option<&D>],
+        _ranks: &mut Vec<Option<u64>>,
+    ) {
+    }
+    fn __call(
+        &self,
+        _index: usize,
+        _anys: &[&(dyn core::any::Any + 'static)],
+        _objects: &[Option<&D>],
+    ) -> Option<R> {
+        None
+    }
+}
+
+// the position of the most specific overload, the first one of those ranked the same
+#[doc(hidden)]
+pub fn __most_specific(ranks: &[Option<u64>]) -> Option<usize> {
+    let mut best: Option<(usize, u64)> = None;
+    for (index, rank) in ranks.iter().enumerate() {
+        if let Some(rank) = *rank {
+            match best {
+                Some((_, top)) if top >= rank => {}
+                _ => best = Some((index, rank)),
+            }
+        }
+    }
+    best.map(|(index, _)| index)
+}
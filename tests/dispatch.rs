@@ -0,0 +1,112 @@
+#![feature(fn_traits, unboxed_closures)]
+use overloadf::*;
+use std::any::Any;
+
+pub trait Shape: Any {}
+
+pub struct Circle;
+pub struct Square;
+
+impl Shape for Circle {}
+impl Shape for Square {}
+
+overload_set! {
+    // no overload takes trait objects, so the first one names their trait
+    #[overload(dynamic = Shape)]
+    pub fn collide(_a: &Circle, _b: &Square) -> &'static str {
+        "circle and square"
+    }
+
+    #[overload(dynamic)]
+    pub fn collide(_a: &Square, _b: &Circle) -> &'static str {
+        "square and circle"
+    }
+}
+
+#[overload(dynamic)]
+mod shapes {
+    use super::*;
+
+    pub fn count(_shape: &Circle) -> u32 {
+        1
+    }
+
+    pub fn count(_shapes: &Square, _other: &Circle) -> u32 {
+        2
+    }
+
+    // parameters named like the bindings of the generated code
+    pub fn count(anys: &dyn Shape, objects: &dyn Shape) -> u32 {
+        0
+    }
+}
+
+#[test]
+fn concrete_overloads() {
+    let circle: Box<dyn Shape> = Box::new(Circle);
+    let square: Box<dyn Shape> = Box::new(Square);
+    assert_eq!(
+        collide.dispatch((&*circle, &*square)),
+        Ok("circle and square")
+    );
+    assert_eq!(
+        collide.dispatch((&*square, &*circle)),
+        Ok("square and circle")
+    );
+    let error = collide.dispatch((&*circle, &*circle)).unwrap_err();
+    assert_eq!(error.set, "collide");
+    assert_eq!(
+        error.arguments,
+        [Any::type_id(&Circle), Any::type_id(&Circle)]
+    );
+    assert_eq!(error.candidates.len(), 2);
+    assert!(collide.dispatch((&*circle,)).is_err());
+}
+
+#[test]
+fn fallback_overload() {
+    let circle: Box<dyn Shape> = Box::new(Circle);
+    let square: Box<dyn Shape> = Box::new(Square);
+    assert_eq!(shapes::count.dispatch((&*circle,)), Ok(1));
+    assert_eq!(shapes::count.dispatch((&*square, &*circle)), Ok(2));
+    assert_eq!(shapes::count.dispatch((&*circle, &*circle)), Ok(0));
+    assert_eq!(shapes::count(&*square, &*circle), 2);
+    assert_eq!(shapes::count(&*square, &*square), 0);
+    assert!(shapes::count.dispatch((&*square,)).is_err());
+}
+
+pub struct Sides<const N: usize>;
+
+impl<const N: usize> Shape for Sides<N> {}
+
+// more overloads than a set used to dispatch to
+macro_rules! sides {
+    ($($n:literal)*) => {
+        #[allow(deprecated)]
+        #[overload(dynamic)]
+        pub fn sides(_shape: &dyn Shape) -> usize {
+            usize::MAX
+        }
+        $(
+            #[allow(deprecated)]
+            #[overload(dynamic)]
+            pub fn sides(_shape: &Sides<$n>) -> usize {
+                $n
+            }
+        )*
+    };
+}
+
+sides!(
+    0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32
+    33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48 49 50 51 52 53 54 55 56 57 58 59 60 61 62
+    63 64 65 66 67 68 69
+);
+
+#[test]
+fn many_overloads() {
+    let shape: Box<dyn Shape> = Box::new(Sides::<69>);
+    assert_eq!(sides(&*shape), 69);
+    assert_eq!(sides.dispatch((&*shape,)), Ok(69));
+    assert_eq!(sides(&Circle as &dyn Shape), usize::MAX);
+}
@@ -0,0 +1,30 @@
+// overload sets used from another crate by the tests of overloadf. they are built with the
+// stable backend, so that `cargo +stable test --test stable` builds this crate too.
+use overloadf::*;
+
+overload_set! {
+    #[overload(stable, macro = crate)]
+    pub fn area(side: u32) -> u32 {
+        side * side
+    }
+    #[overload(stable)]
+    pub const fn area(width: u32, height: u32) -> u32 {
+        width * height
+    }
+}
+
+// a set named like the one at the crate root
+pub mod circles {
+    use overloadf::*;
+
+    overload_set! {
+        #[overload(stable, macro = crate::circles)]
+        pub fn area(radius: f64) -> f64 {
+            3.0 * radius * radius
+        }
+        #[overload(stable)]
+        pub fn area(radius: u32) -> u32 {
+            3 * radius * radius
+        }
+    }
+}
@@ -0,0 +1,44 @@
+#![feature(fn_traits, unboxed_closures)]
+use overloadf::*;
+
+#[derive(Debug)]
+pub struct Haha {
+    a: u32,
+}
+
+#[overload]
+impl Haha {
+    pub fn normal(&self) -> String {
+        format!("{:?}", self)
+    }
+    pub fn normal(&self, prefix: &str) -> String {
+        format!("{} {:?}", prefix, self)
+    }
+    pub fn normal(&self, x: i32, y: i32) -> i32 {
+        self.a as i32 * x + y
+    }
+    pub fn scaled(&self, x: u32, y: u32) -> u32 {
+        self.a * x + y
+    }
+    pub fn scaled(&self, x: i32, y: i32) -> i32 {
+        self.a as i32 * x - y
+    }
+}
+
+// overloads taking different numbers of arguments take them as one value in method call syntax
+#[test]
+fn mixed_arities() {
+    let haha = Haha { a: 3 };
+    assert_eq!(haha.normal(()), "Haha { a: 3 }");
+    assert_eq!(haha.normal("abc"), "abc Haha { a: 3 }");
+    assert_eq!(haha.normal((1, 2)), 5);
+    assert_eq!(Haha::normal(&haha, 1, 2), 5);
+}
+
+// overloads taking the same number of arguments take them one by one
+#[test]
+fn same_arity() {
+    let haha = Haha { a: 3 };
+    assert_eq!(haha.scaled(1_u32, 2_u32), 5);
+    assert_eq!(haha.scaled(1_i32, 2_i32), 1);
+}
@@ -0,0 +1,133 @@
+#![feature(fn_traits, unboxed_closures, const_trait_impl)]
+use overloadf::*;
+
+// overloads of the same name in different modules are different sets
+#[overload]
+mod a {
+    pub fn parse(number: i32) -> i32 {
+        number * 2
+    }
+
+    pub fn parse(number: u8) -> u8 {
+        number + 1
+    }
+}
+
+#[overload]
+mod b {
+    pub fn parse(text: &str) -> usize {
+        text.len()
+    }
+
+    pub fn parse(flag: bool) -> bool {
+        !flag
+    }
+}
+
+#[test]
+fn sets_by_module() {
+    assert_eq!(a::parse(3_i32), 6);
+    assert_eq!(a::parse(3_u8), 4);
+    assert_eq!(b::parse("abc"), 3);
+    assert!(b::parse(false));
+    assert_eq!(a::parse.overloads().count(), 2);
+    assert_eq!(b::parse.overloads().count(), 2);
+}
+
+#[test]
+fn sets_by_block() {
+    overload_set! {
+        fn twice(number: i32) -> i32 {
+            number * 2
+        }
+        fn twice(text: &str) -> String {
+            text.repeat(2)
+        }
+    }
+    assert_eq!(twice(2), 4);
+    assert_eq!(twice("ab"), "abab");
+    {
+        overload_set! {
+            fn twice(number: u8) -> u8 {
+                number * 2
+            }
+            fn twice(flag: bool) -> [bool; 2] {
+                [flag; 2]
+            }
+        }
+        assert_eq!(twice(2_u8), 4);
+        assert_eq!(twice(true), [true, true]);
+    }
+}
+
+// overloads generated by a `macro_rules!` macro share the place of their attribute
+macro_rules! halve {
+    ($ty:ty) => {
+        #[allow(deprecated)]
+        #[overload]
+        pub fn halve(number: $ty) -> $ty {
+            number / 2
+        }
+    };
+}
+
+halve!(i32);
+halve!(u8);
+
+#[test]
+fn sets_by_macro() {
+    assert_eq!(halve(6_i32), 3);
+    assert_eq!(halve(6_u8), 3);
+    assert_eq!(halve.overloads().count(), 2);
+}
+
+// more overloads than a set used to look for
+macro_rules! length {
+    ($($n:literal)*) => {
+        $(
+            #[allow(deprecated)]
+            #[overload]
+            pub fn length(array: [u8; $n]) -> usize {
+                array.len()
+            }
+        )*
+    };
+}
+
+length!(
+    0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32
+    33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48 49 50 51 52 53 54 55 56 57 58 59 60 61 62
+    63 64 65 66 67 68 69
+);
+
+#[test]
+fn many_overloads() {
+    assert_eq!(length([0; 69]), 69);
+    assert_eq!(length.count(), 70);
+    let args: Vec<Box<dyn std::any::Any>> = vec![Box::new([0_u8; 68])];
+    let result = length.call_dyn(args).unwrap();
+    assert_eq!(result.downcast_ref::<usize>(), Some(&68));
+}
+
+// a call macro reaching its set through `$crate`, from outside the module of the set
+pub mod geometry {
+    use overloadf::*;
+
+    overload_set! {
+        #[overload(macro = crate::geometry)]
+        pub fn scale(factor: u32) -> u32 {
+            factor * 2
+        }
+        pub const fn scale(factor: u32, by: u32) -> u32 {
+            factor * by
+        }
+    }
+}
+
+#[test]
+fn macros_by_path() {
+    assert_eq!(geometry::scale!(3), 6);
+    // the only overload taking two arguments is called directly
+    const SCALED: u32 = geometry::scale!(3, 4);
+    assert_eq!(SCALED, 12);
+}
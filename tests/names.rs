@@ -0,0 +1,59 @@
+#![feature(fn_traits, unboxed_closures)]
+// the names bound by the code of the deprecated `#[overload] fn`
+#![allow(deprecated)]
+use overloadf::*;
+
+// sets named like the bindings of the generated code
+#[overload]
+pub fn f(number: i32) -> i32 {
+    number + 1
+}
+
+#[overload]
+pub fn f(text: &str) -> usize {
+    text.len()
+}
+
+#[overload]
+pub fn index(number: i32) -> i32 {
+    number * 2
+}
+
+#[overload]
+pub fn index(flag: bool) -> bool {
+    !flag
+}
+
+#[overload]
+pub fn set(number: i32) -> i32 {
+    number - 1
+}
+
+#[overload]
+pub fn set(text: &'static str) -> &'static str {
+    text
+}
+
+#[overload]
+pub fn result(number: i32) -> i32 {
+    number * 3
+}
+
+#[overload]
+pub fn result(flag: bool) -> bool {
+    flag
+}
+
+#[test]
+fn generated_names() {
+    assert_eq!(f(1), 2);
+    assert_eq!(f("ab"), 2);
+    assert_eq!(index(2), 4);
+    assert!(index(false));
+    assert_eq!(format!("{:?}", f), "f [fn f(number: i32) -> i32, fn f(text: &str) -> usize]");
+    assert_eq!(index.count(), 2);
+    let called = set.call_dyn(vec![Box::new(3_i32)]).unwrap();
+    assert_eq!(called.downcast_ref::<i32>(), Some(&2));
+    let called = result.call_dyn(vec![Box::new(true)]).unwrap();
+    assert_eq!(called.downcast_ref::<bool>(), Some(&true));
+}
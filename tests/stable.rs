@@ -0,0 +1,261 @@
+// the stable backend: overload sets implement their sealed dispatch trait instead of the Fn*
+// traits, and are called through `call` or the call macro. builds on any toolchain, run with
+// `cargo +stable test --test stable` to check that no nightly feature is needed.
+use overloadf::*;
+
+overload_set! {
+    #[overload(stable)]
+    pub fn xdd(number: i64) -> i64 {
+        number * 3
+    }
+    #[overload(stable, macro)]
+    pub fn xdd(number: i32, scale: u8 = 2) -> i32 {
+        number * scale as i32
+    }
+    #[overload(stable)]
+    pub fn xdd(text: &str) -> usize {
+        text.len()
+    }
+}
+
+#[test]
+fn free_functions() {
+    assert_eq!(xdd.call((3_i64,)), 9);
+    assert_eq!(xdd.call((3_i32,)), 6);
+    assert_eq!(xdd.call((3_i32, 4_u8)), 12);
+    assert_eq!(xdd.call(("abc",)), 3);
+    assert_eq!(xdd!(3_i32, 2_u8), 6);
+    assert_eq!(xdd.count(), 3);
+}
+
+#[overload(stable)]
+mod grouped {
+    pub fn parse(text: &str) -> usize {
+        text.len()
+    }
+    pub fn parse(number: u8) -> u8 {
+        number + 1
+    }
+}
+
+#[test]
+fn modules() {
+    assert_eq!(grouped::parse.call(("abc",)), 3);
+    assert_eq!(grouped::parse.call((3_u8,)), 4);
+}
+
+overload_set! {
+    #[overload(stable)]
+    fn area(width: u32, height: u32 = 2) -> u32 {
+        width * height
+    }
+    #[overload(stable)]
+    fn area(side: f64) -> f64 {
+        side * side
+    }
+}
+
+#[test]
+fn sets() {
+    assert_eq!(area.call((3_u32,)), 6);
+    assert_eq!(area.call((3_u32, 3_u32)), 9);
+    assert_eq!(area.call((2.0,)), 4.0);
+}
+
+overload_set! {
+    #[overload(stable)]
+    pub unsafe fn read(pointer: *const u64) -> u64 {
+        *pointer * 4
+    }
+    #[overload(stable)]
+    pub unsafe fn read(pointer: *const u8) -> u8 {
+        *pointer + 1
+    }
+}
+
+#[test]
+fn unsafe_functions() {
+    let n = 6_u64;
+    assert_eq!(unsafe { read.call_unchecked((&n as *const u64,)) }, 24);
+}
+
+overload_set! {
+    #[overload(stable)]
+    pub async fn later(number: i32) -> i32 {
+        number + 3
+    }
+    #[overload(stable)]
+    pub async fn later(number: i64) -> i64 {
+        number + 4
+    }
+}
+
+#[test]
+fn async_functions() {
+    assert_eq!(futures::executor::block_on(later.call((3_i32,))), 6);
+    assert_eq!(futures::executor::block_on(later.call((3_i64,))), 7);
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Haha {
+    a: u32,
+    b: i32,
+}
+
+#[overload(stable)]
+impl Haha {
+    pub fn new(b: i32) -> Self {
+        Self { a: 1, b }
+    }
+    pub fn new(a: u32) -> Self {
+        Self { a, b: 2 }
+    }
+    pub fn sum(&self) -> i64 {
+        self.a as i64 + self.b as i64
+    }
+    pub fn sum(&self, extra: i64) -> i64 {
+        self.a as i64 + self.b as i64 + extra
+    }
+    pub fn pair(&self, x: u32, y: u32) -> u32 {
+        self.a * x + y
+    }
+    pub fn pair(&self, x: i32, y: i32) -> i32 {
+        self.b * x + y
+    }
+}
+
+#[test]
+fn inherent_methods() {
+    let haha = Haha::new.call((12_i32,));
+    assert_eq!(haha, Haha { a: 1, b: 12 });
+    assert_eq!(Haha::new.call((9_u32,)), Haha { a: 9, b: 2 });
+    assert_eq!(Haha::sum.call((&haha,)), 13);
+    assert_eq!(haha.sum(()), 13);
+    assert_eq!(haha.sum(1_i64), 14);
+    assert_eq!(haha.pair(2_u32, 1_u32), 3);
+    assert_eq!(haha.pair(2_i32, 1_i32), 25);
+}
+
+#[overload(stable)]
+pub trait Counter: Sized {
+    fn add(&mut self, n: u32);
+    fn add(&mut self, n: u32, times: u32);
+    fn get(&self) -> u32;
+    fn get(&self, offset: u32) -> u32 {
+        self.get(()) + offset
+    }
+    fn set(&mut self, n: u32, times: u32);
+    fn set(&mut self, n: u8, times: u8);
+}
+
+struct Count(u32);
+
+#[overload]
+impl Counter for Count {
+    fn add(&mut self, n: u32) {
+        self.0 += n;
+    }
+    fn add(&mut self, n: u32, times: u32) {
+        self.0 += n * times;
+    }
+    fn get(&self) -> u32 {
+        self.0
+    }
+    fn set(&mut self, n: u32, times: u32) {
+        self.0 = n * times;
+    }
+    fn set(&mut self, n: u8, times: u8) {
+        self.0 = (n * times) as u32 + 1;
+    }
+}
+
+#[test]
+fn trait_methods() {
+    let mut count = Count(0);
+    count.add(2_u32);
+    count.add((3_u32, 2_u32));
+    assert_eq!(count.get(()), 8);
+    assert_eq!(count.get(1_u32), 9);
+    assert_eq!(Count::get.call((&count,)), 8);
+    count.set(2_u32, 3_u32);
+    assert_eq!(count.get(()), 6);
+    count.set(2_u8, 3_u8);
+    assert_eq!(count.get(()), 7);
+}
+
+pub mod shapes {
+    use overloadf::*;
+
+    #[overload(stable)]
+    pub trait Scale: Sized {
+        fn scale(&self, by: u32) -> u32;
+        fn scale(&self, by: u8) -> u32;
+    }
+}
+
+use shapes::Scale as Resize;
+
+struct Side(u32);
+
+#[overload]
+impl Resize for Side {
+    fn scale(&self, by: u32) -> u32 {
+        self.0 * by
+    }
+    fn scale(&self, by: u8) -> u32 {
+        self.0 + by as u32
+    }
+}
+
+struct Edge(u32);
+
+#[overload]
+impl crate::shapes::Scale for Edge {
+    fn scale(&self, by: u32) -> u32 {
+        self.0 * by * 2
+    }
+    fn scale(&self, by: u8) -> u32 {
+        self.0 + by as u32 * 2
+    }
+}
+
+#[test]
+fn trait_paths() {
+    use shapes::*;
+    assert_eq!(Side(3).scale(2_u32), 6);
+    assert_eq!(Side(3).scale(2_u8), 5);
+    assert_eq!(Edge(3).scale(2_u32), 12);
+    assert_eq!(Edge(3).scale(2_u8), 7);
+}
+
+struct Wrapper<T>(T);
+
+#[overload]
+impl<T> shapes::Scale for Wrapper<T>
+where
+    T: Into<u32> + Copy,
+{
+    fn scale(&self, by: u32) -> u32 {
+        self.0.into() * by
+    }
+    fn scale(&self, by: u8) -> u32 {
+        self.0.into() + by as u32
+    }
+}
+
+#[test]
+fn generic_trait_impls() {
+    use shapes::*;
+    assert_eq!(Wrapper(3_u8).scale(2_u32), 6);
+    assert_eq!(Wrapper(3_u16).scale(2_u8), 5);
+}
+
+#[test]
+fn macros_of_other_crates() {
+    assert_eq!(overloadf_helper::area!(3_u32), 9);
+    assert_eq!(overloadf_helper::circles::area!(1.0), 3.0);
+    assert_eq!(overloadf_helper::circles::area!(2_u32), 12);
+    // the only overload taking two arguments is called directly
+    const AREA: u32 = overloadf_helper::area!(2, 3);
+    assert_eq!(AREA, 6);
+}